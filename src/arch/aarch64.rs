@@ -40,6 +40,9 @@ impl ArchitectureOperations for AArch64ArchitectureOperations {
 
     const ARGUMENT_REGISTER_2: &'static str = "x7";
 
+    const STACK_POINTER_REGISTER: Option<&'static str> = Some("sp");
+    const FRAME_POINTER_REGISTER: Option<&'static str> = Some("x29");
+
     fn new(cpu: *mut ConfObject) -> Result<Self> {
         let mut processor_info_v2: ProcessorInfoV2Interface = get_interface(cpu)?;
 
@@ -41,6 +41,11 @@ impl ArchitectureOperations for ARMArchitectureOperations {
 
     const ARGUMENT_REGISTER_2: &'static str = "r7";
 
+    const STACK_POINTER_REGISTER: Option<&'static str> = Some("sp");
+
+    // NOTE: No frame pointer register is set because ARM and Thumb code use different
+    // registers (r11 and r7) as the frame pointer
+
     fn new(cpu: *mut ConfObject) -> Result<Self> {
         let mut processor_info_v2: ProcessorInfoV2Interface = get_interface(cpu)?;
 
@@ -15,6 +15,7 @@ use crate::{
 use anyhow::anyhow;
 use anyhow::{bail, ensure, Error, Result};
 use raw_cstr::AsRawCstr;
+use serde::Serialize;
 use simics::{
     api::{
         read_phys_memory, sys::instruction_handle_t, write_byte, Access, AttrValueType, ConfObject,
@@ -23,7 +24,7 @@ use simics::{
     },
     read_byte,
 };
-use std::{fmt::Debug, str::FromStr};
+use std::{fmt::Debug, mem::size_of, str::FromStr};
 
 pub mod aarch64;
 pub mod arm;
@@ -31,7 +32,7 @@ pub mod risc_v;
 pub mod x86;
 pub mod x86_64;
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Debug, Clone)]
 /// An architecture hint that can be parsed from a string
 pub(crate) enum ArchitectureHint {
     /// The architecture is x86_64
@@ -97,6 +98,42 @@ impl ArchitectureHint {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A named set of exceptions which are commonly treated as solutions, so they do not have to
+/// be enumerated manually by exception number
+pub(crate) enum ExceptionPreset {
+    /// The x86 and x86-64 exceptions which indicate a fatal error in firmware or bare-metal
+    /// software: #DE (0), #UD (6), #DF (8), #TS (10), #NP (11), #SS (12), #GP (13) and
+    /// #PF (14)
+    X86Fatal,
+    /// The RISC-V exceptions which indicate a fatal error: instruction address misaligned
+    /// (0), instruction access fault (1), illegal instruction (2), load address misaligned
+    /// (4), load access fault (5), store address misaligned (6) and store access fault (7)
+    RiscvFatal,
+}
+
+impl ExceptionPreset {
+    /// Return the exception numbers in this preset
+    pub fn exceptions(&self) -> &'static [i64] {
+        match self {
+            ExceptionPreset::X86Fatal => &[0, 6, 8, 10, 11, 12, 13, 14],
+            ExceptionPreset::RiscvFatal => &[0, 1, 2, 4, 5, 6, 7],
+        }
+    }
+}
+
+impl FromStr for ExceptionPreset {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "x86-fatal" | "x86-64-fatal" | "x86_64-fatal" => Self::X86Fatal,
+            "riscv-fatal" | "risc-v-fatal" => Self::RiscvFatal,
+            _ => bail!("Unknown exception preset: {}", s),
+        })
+    }
+}
+
 pub(crate) enum Architecture {
     /// The x86_64 architecture
     X86_64(X86_64ArchitectureOperations),
@@ -110,6 +147,25 @@ pub(crate) enum Architecture {
     Aarch64(AArch64ArchitectureOperations),
 }
 
+impl Architecture {
+    /// Return the exception preset used by default for this architecture, if there is one.
+    /// ARM and AArch64 have no default preset because their exception numbers are specific
+    /// to each processor model.
+    pub fn default_exception_preset(&self) -> Option<ExceptionPreset> {
+        match self {
+            Architecture::X86_64(_) | Architecture::I386(_) => Some(ExceptionPreset::X86Fatal),
+            Architecture::Riscv(_) => Some(ExceptionPreset::RiscvFatal),
+            Architecture::Arm(_) | Architecture::Aarch64(_) => None,
+        }
+    }
+
+    /// Whether logical addresses can be translated with an explicit directory table base on
+    /// this architecture
+    pub fn supports_directory_table_base(&self) -> bool {
+        matches!(self, Architecture::X86_64(_))
+    }
+}
+
 impl Debug for Architecture {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -132,6 +188,8 @@ pub trait ArchitectureOperations {
     const ARGUMENT_REGISTER_1: &'static str;
     const ARGUMENT_REGISTER_2: &'static str;
     const POINTER_WIDTH_OVERRIDE: Option<i32> = None;
+    const STACK_POINTER_REGISTER: Option<&'static str> = None;
+    const FRAME_POINTER_REGISTER: Option<&'static str> = None;
 
     /// Create a new instance of the architecture operations
     fn new(cpu: *mut ConfObject) -> Result<Self>
@@ -167,26 +225,201 @@ pub trait ArchitectureOperations {
     /// Return a mutable reference to the interface for querying CPU cycles and timing
     fn cycle(&mut self) -> &mut CycleInterface;
 
+    /// Return the register number for the integer register with the given name. Returns an
+    /// error if the processor has no register with that name.
+    fn get_register_number(&mut self, name: &str) -> Result<i32> {
+        let number = self.int_register().get_number(name.as_raw_cstr()?)?;
+
+        ensure!(number >= 0, "No integer register named '{name}'");
+
+        Ok(number)
+    }
+
+    /// Read the value of the integer register with the given name
+    fn get_register_value(&mut self, name: &str) -> Result<u64> {
+        let number = self.get_register_number(name)?;
+        Ok(self.int_register().read(number)?)
+    }
+
+    /// Read the values of the integer registers with the given names, in the same order. All
+    /// register numbers are resolved before any register is read, so an unknown name is
+    /// reported without reading any registers.
+    fn get_register_values(&mut self, names: &[&str]) -> Result<Vec<u64>> {
+        let numbers = names
+            .iter()
+            .map(|name| self.get_register_number(name))
+            .collect::<Result<Vec<_>>>()?;
+
+        numbers
+            .into_iter()
+            .map(|number| Ok(self.int_register().read(number)?))
+            .collect()
+    }
+
+    /// Write a value to the integer register with the given name
+    fn set_register_value(&mut self, name: &str, value: u64) -> Result<()> {
+        let number = self.get_register_number(name)?;
+        self.int_register().write(number, value)?;
+        Ok(())
+    }
+
+    /// Return the value of the stack pointer register. Returns an error if the stack pointer
+    /// register is not known for this architecture.
+    fn stack_pointer(&mut self) -> Result<u64> {
+        let name = Self::STACK_POINTER_REGISTER
+            .ok_or_else(|| anyhow!("Stack pointer register is not known for this architecture"))?;
+        self.get_register_value(name)
+    }
+
+    /// Return the value of the frame pointer register. Returns an error if the frame pointer
+    /// register is not known for this architecture.
+    fn frame_pointer(&mut self) -> Result<u64> {
+        let name = Self::FRAME_POINTER_REGISTER
+            .ok_or_else(|| anyhow!("Frame pointer register is not known for this architecture"))?;
+        self.get_register_value(name)
+    }
+
     /// Return the value of the magic index selector register, which is used to determine
     /// whether a magic instruction should be used or skipped.
     fn get_magic_index_selector(&mut self) -> Result<u64> {
-        Ok(self
-            .int_register()
-            .get_number(Self::INDEX_SELECTOR_REGISTER.as_raw_cstr()?)
-            .and_then(|n| self.int_register().read(n))?)
+        self.get_register_value(Self::INDEX_SELECTOR_REGISTER)
+    }
+
+    /// Translate a logical address to a physical address. If a directory table base is
+    /// provided, the translation is performed using that page table instead of the current
+    /// context of the processor, which allows translating addresses in an address space which
+    /// is not currently active. Not all architectures support translating with an explicit
+    /// directory table base.
+    fn logical_to_physical(
+        &mut self,
+        logical_address: u64,
+        directory_table_base: Option<u64>,
+    ) -> Result<u64> {
+        if let Some(directory_table_base) = directory_table_base {
+            bail!(
+                "Translation with an explicit directory table base {directory_table_base:#x} is not supported for this architecture"
+            );
+        }
+
+        let physical_address_block = self
+            .processor_info_v2()
+            .logical_to_physical(logical_address, Access::Sim_Access_Read)?;
+
+        ensure!(
+            physical_address_block.valid != 0,
+            "Invalid linear address {logical_address:#x}"
+        );
+
+        Ok(physical_address_block.address)
+    }
+
+    /// Read the model-specific register with the given index. Only x86 and x86-64 processors
+    /// have model-specific registers.
+    fn read_msr(&mut self, index: u32) -> Result<u64> {
+        bail!("Cannot read MSR {index:#x}: model-specific registers are not supported for this architecture");
+    }
+
+    /// Write the model-specific register with the given index. Only x86 and x86-64
+    /// processors have model-specific registers.
+    fn write_msr(&mut self, index: u32, value: u64) -> Result<()> {
+        bail!("Cannot write {value:#x} to MSR {index:#x}: model-specific registers are not supported for this architecture");
+    }
+
+    /// The logical address whose access caused the exception `exception`, if the exception is
+    /// a page fault and the architecture records the faulting address. Only x86 and x86-64
+    /// page faults (exception 14, with the address in CR2) are supported.
+    fn fault_address(&mut self, _exception: i64) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Write the low `width` bytes of `value` in little-endian byte order to the physical
+    /// memory of the processor at `physical_address`. The width must be between 1 and 8
+    /// bytes, and bits of the value above the width are discarded.
+    fn write_physical_value(
+        &mut self,
+        physical_address: u64,
+        value: u64,
+        width: usize,
+    ) -> Result<()> {
+        ensure!(
+            (1..=size_of::<u64>()).contains(&width),
+            "Invalid width {width} for physical memory write at {physical_address:#x}, must be between 1 and 8 bytes"
+        );
+
+        self.write_physical_memory(physical_address, &value.to_le_bytes()[..width])
+    }
+
+    /// Write `bytes` to the physical memory of the processor starting at `physical_address`
+    fn write_physical_memory(&mut self, physical_address: u64, bytes: &[u8]) -> Result<()> {
+        let physical_memory = self.processor_info_v2().get_physical_memory()?;
+
+        bytes.iter().enumerate().try_for_each(|(i, c)| {
+            let address = physical_address + i as u64;
+            write_byte(physical_memory, address, *c)
+                .map_err(|e| anyhow!("Failed to write byte at {:#x}: {}", address, e))
+        })
+    }
+
+    /// Return whether the physical address is backed by memory or a device in the physical
+    /// memory space of the processor. Accesses to unmapped addresses raise an exception in the
+    /// simulator, so this is checked by attempting to read the byte at the address.
+    fn is_mapped(&mut self, physical_address: u64) -> Result<bool> {
+        let physical_memory = self.processor_info_v2().get_physical_memory()?;
+        Ok(read_byte(physical_memory, physical_address).is_ok())
+    }
+
+    /// Read `len` bytes of memory starting at a logical address in the current context of the
+    /// processor. Each byte is translated separately, so the range may cross page boundaries.
+    fn read_logical_memory(&mut self, logical_address: u64, len: usize) -> Result<Vec<u8>> {
+        (0..len as u64)
+            .map(|i| {
+                let address = logical_address.wrapping_add(i);
+                let physical_address = self.logical_to_physical(address, None)?;
+                read_byte(
+                    self.processor_info_v2().get_physical_memory()?,
+                    physical_address,
+                )
+                .map_err(|e| anyhow!("Failed to read byte at {:#x}: {}", physical_address, e))
+            })
+            .collect::<Result<Vec<_>>>()
+    }
+
+    /// Read `len` bytes of memory starting at `offset` bytes from the address contained in the
+    /// integer register with the given name, for example the memory around the stack pointer
+    fn read_near_register(&mut self, name: &str, offset: i64, len: usize) -> Result<Vec<u8>> {
+        let value = self.get_register_value(name)?;
+        self.read_logical_memory(value.wrapping_add_signed(offset), len)
+    }
+
+    /// The name of the register containing magic start harness argument `index`. This is
+    /// `argument_registers[index]` if it is given, or the architecture's argument register
+    /// otherwise.
+    fn magic_start_argument_register(index: usize, argument_registers: &[String]) -> &str {
+        argument_registers
+            .get(index)
+            .map(|register| register.as_str())
+            .unwrap_or(match index {
+                0 => Self::ARGUMENT_REGISTER_0,
+                1 => Self::ARGUMENT_REGISTER_1,
+                _ => Self::ARGUMENT_REGISTER_2,
+            })
     }
 
     /// Get the magic start information from the harness which takes the arguments:
     ///
     /// - buffer: The address of the buffer containing the testcase
     /// - size_ptr: A pointer to a pointer-sized variable containing the size of the testcase
-    fn get_magic_start_buffer_ptr_size_ptr(&mut self) -> Result<StartInfo> {
-        let buffer_register_number = self
-            .int_register()
-            .get_number(Self::ARGUMENT_REGISTER_0.as_raw_cstr()?)?;
-        let size_ptr_register_number = self
-            .int_register()
-            .get_number(Self::ARGUMENT_REGISTER_1.as_raw_cstr()?)?;
+    ///
+    /// The arguments are read from `argument_registers`, if given, instead of the
+    /// architecture's argument registers
+    fn get_magic_start_buffer_ptr_size_ptr(
+        &mut self,
+        argument_registers: &[String],
+    ) -> Result<StartInfo> {
+        let buffer_register_number =
+            self.get_register_number(Self::magic_start_argument_register(0, argument_registers))?;
+        let size_ptr_register_number =
+            self.get_register_number(Self::magic_start_argument_register(1, argument_registers))?;
         let buffer_logical_address = self.int_register().read(buffer_register_number)?;
         let size_ptr_logical_address = self.int_register().read(size_ptr_register_number)?;
         let buffer_physical_address_block = self
@@ -257,13 +490,17 @@ pub trait ArchitectureOperations {
     ///
     /// - buffer: The address of the buffer containing the testcase
     /// - size_val: The maximum size of the testcase
-    fn get_magic_start_buffer_ptr_size_val(&mut self) -> Result<StartInfo> {
-        let buffer_register_number = self
-            .int_register()
-            .get_number(Self::ARGUMENT_REGISTER_0.as_raw_cstr()?)?;
-        let size_val_register_number = self
-            .int_register()
-            .get_number(Self::ARGUMENT_REGISTER_1.as_raw_cstr()?)?;
+    ///
+    /// The arguments are read from `argument_registers`, if given, instead of the
+    /// architecture's argument registers
+    fn get_magic_start_buffer_ptr_size_val(
+        &mut self,
+        argument_registers: &[String],
+    ) -> Result<StartInfo> {
+        let buffer_register_number =
+            self.get_register_number(Self::magic_start_argument_register(0, argument_registers))?;
+        let size_val_register_number =
+            self.get_register_number(Self::magic_start_argument_register(1, argument_registers))?;
         let buffer_logical_address = self.int_register().read(buffer_register_number)?;
         let size_val = self.int_register().read(size_val_register_number)?;
         let buffer_physical_address_block = self
@@ -309,16 +546,19 @@ pub trait ArchitectureOperations {
     /// - buffer: The address of the buffer containing the testcase
     /// - size_ptr: A pointer to a pointer-sized variable to which the size is written
     /// - size_val: The maximum size of the testcase
-    fn get_magic_start_buffer_ptr_size_ptr_val(&mut self) -> Result<StartInfo> {
-        let buffer_register_number = self
-            .int_register()
-            .get_number(Self::ARGUMENT_REGISTER_0.as_raw_cstr()?)?;
-        let size_ptr_register_number = self
-            .int_register()
-            .get_number(Self::ARGUMENT_REGISTER_1.as_raw_cstr()?)?;
-        let size_val_register_number = self
-            .int_register()
-            .get_number(Self::ARGUMENT_REGISTER_2.as_raw_cstr()?)?;
+    ///
+    /// The arguments are read from `argument_registers`, if given, instead of the
+    /// architecture's argument registers
+    fn get_magic_start_buffer_ptr_size_ptr_val(
+        &mut self,
+        argument_registers: &[String],
+    ) -> Result<StartInfo> {
+        let buffer_register_number =
+            self.get_register_number(Self::magic_start_argument_register(0, argument_registers))?;
+        let size_ptr_register_number =
+            self.get_register_number(Self::magic_start_argument_register(1, argument_registers))?;
+        let size_val_register_number =
+            self.get_register_number(Self::magic_start_argument_register(2, argument_registers))?;
 
         let buffer_logical_address = self.int_register().read(buffer_register_number)?;
         let size_ptr_logical_address = self.int_register().read(size_ptr_register_number)?;
@@ -381,25 +621,14 @@ pub trait ArchitectureOperations {
     /// the manual start functionality
     fn get_manual_start_info(&mut self, info: &ManualStartInfo) -> Result<StartInfo> {
         let buffer_physical_address = if matches!(info.address, ManualStartAddress::Virtual(_)) {
-            let physical_address_block = self
-                .processor_info_v2()
-                // NOTE: Do we need to support segmented memory via logical_to_physical?
-                .logical_to_physical(
-                    match info.address {
-                        ManualStartAddress::Virtual(address) => address,
-                        ManualStartAddress::Physical(address) => address,
-                    },
-                    Access::Sim_Access_Read,
-                )?;
-
-            if physical_address_block.valid == 0 {
-                bail!(
-                    "Invalid linear address for given buffer address {:?}",
-                    info.address
-                );
-            }
-
-            physical_address_block.address
+            // NOTE: Do we need to support segmented memory via logical_to_physical?
+            self.logical_to_physical(info.address.address(), info.directory_table_base)
+                .map_err(|e| {
+                    anyhow!(
+                        "Invalid linear address for given buffer address {:?}: {e}",
+                        info.address
+                    )
+                })?
         } else {
             info.address.address()
         };
@@ -409,17 +638,12 @@ pub trait ArchitectureOperations {
         let size = match &info.size {
             crate::ManualStartSize::SizePtr { address } => {
                 let address = match address {
-                    ManualStartAddress::Virtual(v) => {
-                        let physical_address = self
-                            .processor_info_v2()
-                            .logical_to_physical(*v, Access::Sim_Access_Read)?;
-
-                        if physical_address.valid == 0 {
-                            bail!("Invalid linear address given for start buffer : {v:#x}");
-                        }
-
-                        StartPhysicalAddress::WasVirtual(physical_address.address)
-                    }
+                    ManualStartAddress::Virtual(v) => StartPhysicalAddress::WasVirtual(
+                        self.logical_to_physical(*v, info.directory_table_base)
+                            .map_err(|e| {
+                                anyhow!("Invalid linear address given for start buffer: {e}")
+                            })?,
+                    ),
                     ManualStartAddress::Physical(p) => StartPhysicalAddress::WasPhysical(*p),
                 };
 
@@ -441,17 +665,12 @@ pub trait ArchitectureOperations {
                 maximum_size,
             } => {
                 let address = match address {
-                    ManualStartAddress::Virtual(v) => {
-                        let physical_address = self
-                            .processor_info_v2()
-                            .logical_to_physical(*v, Access::Sim_Access_Read)?;
-
-                        if physical_address.valid == 0 {
-                            bail!("Invalid linear address given for start buffer : {v:#x}");
-                        }
-
-                        StartPhysicalAddress::WasVirtual(physical_address.address)
-                    }
+                    ManualStartAddress::Virtual(v) => StartPhysicalAddress::WasVirtual(
+                        self.logical_to_physical(*v, info.directory_table_base)
+                            .map_err(|e| {
+                                anyhow!("Invalid linear address given for start buffer: {e}")
+                            })?,
+                    ),
                     ManualStartAddress::Physical(p) => StartPhysicalAddress::WasPhysical(*p),
                 };
 
@@ -491,26 +710,12 @@ pub trait ArchitectureOperations {
         let addr_size =
             self.processor_info_v2().get_logical_address_width()? as usize / u8::BITS as usize;
 
-        let physical_memory = self.processor_info_v2().get_physical_memory()?;
-
         testcase.truncate(info.size.maximum_size());
 
-        testcase.iter().enumerate().try_for_each(|(i, c)| {
-            let physical_address = info.address.physical_address() + (i as u64);
-            write_byte(physical_memory, physical_address, *c)
-        })?;
+        self.write_physical_memory(info.address.physical_address(), &testcase)?;
 
         if let Some(size_address) = info.size.physical_address().map(|s| s.physical_address()) {
-            testcase
-                .len()
-                .to_le_bytes()
-                .iter()
-                .take(addr_size)
-                .enumerate()
-                .try_for_each(|(i, c)| {
-                    let physical_address = size_address + (i as u64);
-                    write_byte(physical_memory, physical_address, *c)
-                })?;
+            self.write_physical_value(size_address, testcase.len() as u64, addr_size)?;
         }
 
         Ok(())
@@ -615,6 +820,56 @@ impl ArchitectureOperations for Architecture {
         }
     }
 
+    fn stack_pointer(&mut self) -> Result<u64> {
+        match self {
+            Architecture::X86_64(x86_64) => x86_64.stack_pointer(),
+            Architecture::I386(i386) => i386.stack_pointer(),
+            Architecture::Riscv(riscv) => riscv.stack_pointer(),
+            Architecture::Arm(arm) => arm.stack_pointer(),
+            Architecture::Aarch64(aarch64) => aarch64.stack_pointer(),
+        }
+    }
+
+    fn frame_pointer(&mut self) -> Result<u64> {
+        match self {
+            Architecture::X86_64(x86_64) => x86_64.frame_pointer(),
+            Architecture::I386(i386) => i386.frame_pointer(),
+            Architecture::Riscv(riscv) => riscv.frame_pointer(),
+            Architecture::Arm(arm) => arm.frame_pointer(),
+            Architecture::Aarch64(aarch64) => aarch64.frame_pointer(),
+        }
+    }
+
+    fn read_msr(&mut self, index: u32) -> Result<u64> {
+        match self {
+            Architecture::X86_64(x86_64) => x86_64.read_msr(index),
+            Architecture::I386(i386) => i386.read_msr(index),
+            Architecture::Riscv(riscv) => riscv.read_msr(index),
+            Architecture::Arm(arm) => arm.read_msr(index),
+            Architecture::Aarch64(aarch64) => aarch64.read_msr(index),
+        }
+    }
+
+    fn fault_address(&mut self, exception: i64) -> Result<Option<u64>> {
+        match self {
+            Architecture::X86_64(x86_64) => x86_64.fault_address(exception),
+            Architecture::I386(i386) => i386.fault_address(exception),
+            Architecture::Riscv(riscv) => riscv.fault_address(exception),
+            Architecture::Arm(arm) => arm.fault_address(exception),
+            Architecture::Aarch64(aarch64) => aarch64.fault_address(exception),
+        }
+    }
+
+    fn write_msr(&mut self, index: u32, value: u64) -> Result<()> {
+        match self {
+            Architecture::X86_64(x86_64) => x86_64.write_msr(index, value),
+            Architecture::I386(i386) => i386.write_msr(index, value),
+            Architecture::Riscv(riscv) => riscv.write_msr(index, value),
+            Architecture::Arm(arm) => arm.write_msr(index, value),
+            Architecture::Aarch64(aarch64) => aarch64.write_msr(index, value),
+        }
+    }
+
     fn get_magic_index_selector(&mut self) -> Result<u64> {
         match self {
             Architecture::X86_64(x86_64) => x86_64.get_magic_index_selector(),
@@ -625,33 +880,92 @@ impl ArchitectureOperations for Architecture {
         }
     }
 
-    fn get_magic_start_buffer_ptr_size_ptr(&mut self) -> Result<StartInfo> {
+    fn logical_to_physical(
+        &mut self,
+        logical_address: u64,
+        directory_table_base: Option<u64>,
+    ) -> Result<u64> {
         match self {
-            Architecture::X86_64(x86_64) => x86_64.get_magic_start_buffer_ptr_size_ptr(),
-            Architecture::I386(i386) => i386.get_magic_start_buffer_ptr_size_ptr(),
-            Architecture::Riscv(riscv) => riscv.get_magic_start_buffer_ptr_size_ptr(),
-            Architecture::Arm(arm) => arm.get_magic_start_buffer_ptr_size_ptr(),
-            Architecture::Aarch64(aarch64) => aarch64.get_magic_start_buffer_ptr_size_ptr(),
+            Architecture::X86_64(x86_64) => {
+                x86_64.logical_to_physical(logical_address, directory_table_base)
+            }
+            Architecture::I386(i386) => {
+                i386.logical_to_physical(logical_address, directory_table_base)
+            }
+            Architecture::Riscv(riscv) => {
+                riscv.logical_to_physical(logical_address, directory_table_base)
+            }
+            Architecture::Arm(arm) => {
+                arm.logical_to_physical(logical_address, directory_table_base)
+            }
+            Architecture::Aarch64(aarch64) => {
+                aarch64.logical_to_physical(logical_address, directory_table_base)
+            }
         }
     }
 
-    fn get_magic_start_buffer_ptr_size_val(&mut self) -> Result<StartInfo> {
+    fn get_magic_start_buffer_ptr_size_ptr(
+        &mut self,
+        argument_registers: &[String],
+    ) -> Result<StartInfo> {
         match self {
-            Architecture::X86_64(x86_64) => x86_64.get_magic_start_buffer_ptr_size_val(),
-            Architecture::I386(i386) => i386.get_magic_start_buffer_ptr_size_val(),
-            Architecture::Riscv(riscv) => riscv.get_magic_start_buffer_ptr_size_val(),
-            Architecture::Arm(arm) => arm.get_magic_start_buffer_ptr_size_val(),
-            Architecture::Aarch64(aarch64) => aarch64.get_magic_start_buffer_ptr_size_val(),
+            Architecture::X86_64(x86_64) => {
+                x86_64.get_magic_start_buffer_ptr_size_ptr(argument_registers)
+            }
+            Architecture::I386(i386) => {
+                i386.get_magic_start_buffer_ptr_size_ptr(argument_registers)
+            }
+            Architecture::Riscv(riscv) => {
+                riscv.get_magic_start_buffer_ptr_size_ptr(argument_registers)
+            }
+            Architecture::Arm(arm) => arm.get_magic_start_buffer_ptr_size_ptr(argument_registers),
+            Architecture::Aarch64(aarch64) => {
+                aarch64.get_magic_start_buffer_ptr_size_ptr(argument_registers)
+            }
         }
     }
 
-    fn get_magic_start_buffer_ptr_size_ptr_val(&mut self) -> Result<StartInfo> {
+    fn get_magic_start_buffer_ptr_size_val(
+        &mut self,
+        argument_registers: &[String],
+    ) -> Result<StartInfo> {
         match self {
-            Architecture::X86_64(x86_64) => x86_64.get_magic_start_buffer_ptr_size_ptr(),
-            Architecture::I386(i386) => i386.get_magic_start_buffer_ptr_size_ptr(),
-            Architecture::Riscv(riscv) => riscv.get_magic_start_buffer_ptr_size_ptr(),
-            Architecture::Arm(arm) => arm.get_magic_start_buffer_ptr_size_ptr_val(),
-            Architecture::Aarch64(aarch64) => aarch64.get_magic_start_buffer_ptr_size_ptr_val(),
+            Architecture::X86_64(x86_64) => {
+                x86_64.get_magic_start_buffer_ptr_size_val(argument_registers)
+            }
+            Architecture::I386(i386) => {
+                i386.get_magic_start_buffer_ptr_size_val(argument_registers)
+            }
+            Architecture::Riscv(riscv) => {
+                riscv.get_magic_start_buffer_ptr_size_val(argument_registers)
+            }
+            Architecture::Arm(arm) => arm.get_magic_start_buffer_ptr_size_val(argument_registers),
+            Architecture::Aarch64(aarch64) => {
+                aarch64.get_magic_start_buffer_ptr_size_val(argument_registers)
+            }
+        }
+    }
+
+    fn get_magic_start_buffer_ptr_size_ptr_val(
+        &mut self,
+        argument_registers: &[String],
+    ) -> Result<StartInfo> {
+        match self {
+            Architecture::X86_64(x86_64) => {
+                x86_64.get_magic_start_buffer_ptr_size_ptr(argument_registers)
+            }
+            Architecture::I386(i386) => {
+                i386.get_magic_start_buffer_ptr_size_ptr(argument_registers)
+            }
+            Architecture::Riscv(riscv) => {
+                riscv.get_magic_start_buffer_ptr_size_ptr(argument_registers)
+            }
+            Architecture::Arm(arm) => {
+                arm.get_magic_start_buffer_ptr_size_ptr_val(argument_registers)
+            }
+            Architecture::Aarch64(aarch64) => {
+                aarch64.get_magic_start_buffer_ptr_size_ptr_val(argument_registers)
+            }
         }
     }
 
@@ -695,3 +1009,24 @@ impl ArchitectureOperations for Architecture {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::ExceptionPreset;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_exception_presets() {
+        assert_eq!(
+            ExceptionPreset::from_str("x86-fatal").unwrap().exceptions(),
+            &[0, 6, 8, 10, 11, 12, 13, 14]
+        );
+        assert_eq!(
+            ExceptionPreset::from_str("riscv-fatal")
+                .unwrap()
+                .exceptions(),
+            &[0, 1, 2, 4, 5, 6, 7]
+        );
+        assert!(ExceptionPreset::from_str("arm-fatal").is_err());
+    }
+}
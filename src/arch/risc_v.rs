@@ -41,6 +41,9 @@ impl ArchitectureOperations for RISCVArchitectureOperations {
 
     const ARGUMENT_REGISTER_2: &'static str = "x13";
 
+    const STACK_POINTER_REGISTER: Option<&'static str> = Some("x2");
+    const FRAME_POINTER_REGISTER: Option<&'static str> = Some("x8");
+
     fn new(cpu: *mut ConfObject) -> Result<Self> {
         let mut processor_info_v2: ProcessorInfoV2Interface = get_interface(cpu)?;
 
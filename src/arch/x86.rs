@@ -5,7 +5,10 @@
 
 use std::{ffi::CStr, mem::size_of, slice::from_raw_parts};
 
-use super::ArchitectureOperations;
+use super::{
+    x86_64::{read_msr, write_msr, PAGE_FAULT_EXCEPTION},
+    ArchitectureOperations,
+};
 use crate::{
     tracer::{CmpExpr, CmpType, CmpValue, TraceEntry},
     traits::TracerDisassembler,
@@ -40,6 +43,8 @@ impl ArchitectureOperations for X86ArchitectureOperations {
     const ARGUMENT_REGISTER_1: &'static str = "edx";
     const ARGUMENT_REGISTER_2: &'static str = "ecx";
     const POINTER_WIDTH_OVERRIDE: Option<i32> = Some(4);
+    const STACK_POINTER_REGISTER: Option<&'static str> = Some("esp");
+    const FRAME_POINTER_REGISTER: Option<&'static str> = Some("ebp");
 
     fn new(cpu: *mut ConfObject) -> Result<Self> {
         let mut processor_info_v2: ProcessorInfoV2Interface = get_interface(cpu)?;
@@ -166,6 +171,22 @@ impl ArchitectureOperations for X86ArchitectureOperations {
         &mut self.cycle
     }
 
+    fn read_msr(&mut self, index: u32) -> Result<u64> {
+        read_msr(self.cpu, index)
+    }
+
+    fn fault_address(&mut self, exception: i64) -> Result<Option<u64>> {
+        if exception == PAGE_FAULT_EXCEPTION {
+            Ok(Some(self.get_register_value("cr2")?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn write_msr(&mut self, index: u32, value: u64) -> Result<()> {
+        write_msr(self.cpu, index, value)
+    }
+
     fn trace_pc(&mut self, instruction_query: *mut instruction_handle_t) -> Result<TraceEntry> {
         let instruction_bytes = self
             .cpu_instruction_query
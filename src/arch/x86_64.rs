@@ -6,16 +6,18 @@
 use std::{ffi::CStr, mem::size_of, slice::from_raw_parts};
 
 use crate::{
+    os::windows::util::virtual_to_physical,
     tracer::{CmpExpr, CmpType, CmpValue, TraceEntry},
     traits::TracerDisassembler,
 };
-use anyhow::{anyhow, bail, Error, Result};
+use anyhow::{anyhow, bail, ensure, Error, Result};
 use libafl::prelude::CmpValues;
 use raw_cstr::AsRawCstr;
 use simics::api::{
-    get_interface, read_phys_memory, sys::instruction_handle_t, Access, ConfObject,
-    CpuInstructionQueryInterface, CpuInstrumentationSubscribeInterface, CycleInterface,
-    IntRegisterInterface, ProcessorInfoV2Interface,
+    get_interface, read_phys_memory,
+    sys::{instruction_handle_t, x86_msr_access_status_t, x86_msr_access_type_t},
+    Access, ConfObject, CpuInstructionQueryInterface, CpuInstrumentationSubscribeInterface,
+    CycleInterface, IntRegisterInterface, ProcessorInfoV2Interface, X86MsrInterface,
 };
 use yaxpeax_x86::amd64::{ConditionCode, InstDecoder, Instruction, Opcode, Operand};
 
@@ -36,6 +38,8 @@ impl ArchitectureOperations for X86_64ArchitectureOperations {
     const ARGUMENT_REGISTER_0: &'static str = "rsi";
     const ARGUMENT_REGISTER_1: &'static str = "rdx";
     const ARGUMENT_REGISTER_2: &'static str = "rcx";
+    const STACK_POINTER_REGISTER: Option<&'static str> = Some("rsp");
+    const FRAME_POINTER_REGISTER: Option<&'static str> = Some("rbp");
 
     fn new(cpu: *mut ConfObject) -> Result<Self> {
         let mut processor_info_v2: ProcessorInfoV2Interface = get_interface(cpu)?;
@@ -139,6 +143,44 @@ impl ArchitectureOperations for X86_64ArchitectureOperations {
         &mut self.cycle
     }
 
+    fn logical_to_physical(
+        &mut self,
+        logical_address: u64,
+        directory_table_base: Option<u64>,
+    ) -> Result<u64> {
+        if let Some(directory_table_base) = directory_table_base {
+            // Walk the 4-level page table rooted at the given directory table base (CR3) so
+            // addresses can be translated in an address space other than the current one
+            virtual_to_physical(self.cpu, directory_table_base, logical_address)
+        } else {
+            let physical_address_block = self
+                .processor_info_v2
+                .logical_to_physical(logical_address, Access::Sim_Access_Read)?;
+
+            if physical_address_block.valid == 0 {
+                bail!("Invalid linear address {logical_address:#x}");
+            }
+
+            Ok(physical_address_block.address)
+        }
+    }
+
+    fn read_msr(&mut self, index: u32) -> Result<u64> {
+        read_msr(self.cpu, index)
+    }
+
+    fn fault_address(&mut self, exception: i64) -> Result<Option<u64>> {
+        if exception == PAGE_FAULT_EXCEPTION {
+            Ok(Some(self.get_register_value("cr2")?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn write_msr(&mut self, index: u32, value: u64) -> Result<()> {
+        write_msr(self.cpu, index, value)
+    }
+
     fn trace_pc(&mut self, instruction_query: *mut instruction_handle_t) -> Result<TraceEntry> {
         let instruction_bytes = self
             .cpu_instruction_query
@@ -1027,3 +1069,39 @@ impl TracerDisassembler for Disassembler {
         vec![]
     }
 }
+
+/// The exception number of the page fault exception (#PF) on x86 and x86-64 processors
+pub(crate) const PAGE_FAULT_EXCEPTION: i64 = 14;
+
+/// Read a model-specific register on an x86 or x86-64 processor without side effects
+pub(crate) fn read_msr(cpu: *mut ConfObject, index: u32) -> Result<u64> {
+    let mut x86_msr: X86MsrInterface = get_interface(cpu)?;
+    let result = x86_msr.get(
+        index as i64,
+        x86_msr_access_type_t::Sim_X86_Msr_Attribute_Access,
+    )?;
+
+    ensure!(
+        result.status == x86_msr_access_status_t::Sim_X86_Msr_Ok,
+        "Failed to read MSR {index:#x}"
+    );
+
+    Ok(result.value)
+}
+
+/// Write a model-specific register on an x86 or x86-64 processor without side effects
+pub(crate) fn write_msr(cpu: *mut ConfObject, index: u32, value: u64) -> Result<()> {
+    let mut x86_msr: X86MsrInterface = get_interface(cpu)?;
+    let status = x86_msr.set(
+        index as i64,
+        value,
+        x86_msr_access_type_t::Sim_X86_Msr_Attribute_Access,
+    )?;
+
+    ensure!(
+        status == x86_msr_access_status_t::Sim_X86_Msr_Ok,
+        "Failed to write MSR {index:#x}"
+    );
+
+    Ok(())
+}
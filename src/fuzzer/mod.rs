@@ -10,16 +10,17 @@ use crate::{
     },
     Tsffs,
 };
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, ensure, Result};
 use libafl::{
-    feedback_or, feedback_or_fast,
+    feedback_and_fast, feedback_or, feedback_or_fast,
     inputs::{HasBytesVec, Input},
     prelude::{
         havoc_mutations, ondisk::OnDiskMetadataFormat, tokens_mutations, AFLppRedQueen, BytesInput,
-        CachedOnDiskCorpus, Corpus, CrashFeedback, ExitKind, HasCurrentCorpusIdx, HasTargetBytes,
-        HitcountsMapObserver, I2SRandReplace, MaxMapFeedback, OnDiskCorpus, RandBytesGenerator,
-        SimpleEventManager, SimpleMonitor, StdCmpValuesObserver, StdMOptMutator, StdMapObserver,
-        StdScheduledMutator, TimeFeedback, TimeObserver, Tokens,
+        CachedOnDiskCorpus, ConstFeedback, Corpus, CrashFeedback, ExitKind, HasCurrentCorpusIdx,
+        HasTargetBytes, HitcountsMapObserver, I2SRandReplace, MaxMapFeedback, OnDiskCorpus,
+        RandBytesGenerator, SimpleEventManager, SimpleMonitor, StdCmpValuesObserver,
+        StdMOptMutator, StdMapObserver, StdScheduledMutator, TimeFeedback, TimeObserver,
+        TimeoutFeedback, Tokens,
     },
     schedulers::{
         powersched::PowerSchedule, IndexesLenTimeMinimizerScheduler, StdWeightedScheduler,
@@ -29,7 +30,7 @@ use libafl::{
         GeneralizationStage, IfStage, StdMutationalStage, StdPowerMutationalStage,
         SyncFromDiskStage, TracingStage,
     },
-    state::{HasCorpus, HasMetadata, StdState},
+    state::{HasCorpus, HasMaxSize, HasMetadata, StdState},
     Fuzzer, StdFuzzer,
 };
 use libafl_bolts::{
@@ -42,8 +43,14 @@ use libafl_bolts::{
 use libafl_targets::{AFLppCmpLogObserver, AFLppCmplogTracingStage};
 use simics::{api::AsConfObject, debug, trace, warn};
 use std::{
-    cell::RefCell, fmt::Debug, fs::write, io::stderr, slice::from_raw_parts_mut,
-    sync::mpsc::channel, thread::spawn,
+    cell::RefCell,
+    fmt::Debug,
+    fs::write,
+    io::stderr,
+    slice::from_raw_parts_mut,
+    sync::mpsc::{channel, RecvTimeoutError},
+    thread::spawn,
+    time::Duration,
 };
 use tokenize::{tokenize_executable_file, tokenize_src_file};
 use tracing::{level_filters::LevelFilter, Level};
@@ -90,6 +97,11 @@ impl Debug for Testcase {
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub(crate) struct ShutdownMessage {}
 
+/// The tracing target of messages logging the testcases and execution results exchanged
+/// between the fuzzer thread and the simulation. This is a free constant because tracing
+/// targets are used in statics, which cannot refer to `Self`.
+const FUZZER_CHANNEL_LOG_TARGET: &str = "tsffs::fuzzer_channel";
+
 impl Tsffs {
     const EDGES_OBSERVER_NAME: &'static str = "coverage";
     const AFLPP_CMP_OBSERVER_NAME: &'static str = "aflpp_cmplog";
@@ -128,15 +140,25 @@ impl Tsffs {
 
         let client = RefCell::new((otx, orx));
 
+        ensure!(
+            self.coverage_map_size.is_power_of_two(),
+            "Coverage map size {:#x} is not a power of two",
+            self.coverage_map_size
+        );
+
+        let coverage_map_size = self.coverage_map_size;
+        let _ = self
+            .coverage_map
+            .get_or_init(|| OwnedMutSlice::from(vec![0; coverage_map_size]));
+
         let coverage_map = unsafe {
-            from_raw_parts_mut(
-                self.coverage_map
-                    .get_mut()
-                    .ok_or_else(|| anyhow!("Coverage map not set"))?
-                    .as_mut_slice()
-                    .as_mut_ptr(),
-                Self::COVERAGE_MAP_SIZE,
-            )
+            let coverage_map = self
+                .coverage_map
+                .get_mut()
+                .ok_or_else(|| anyhow!("Coverage map not set"))?
+                .as_mut_slice();
+
+            from_raw_parts_mut(coverage_map.as_mut_ptr(), coverage_map.len())
         };
 
         let aflpp_cmp_map = Box::leak(unsafe {
@@ -158,8 +180,10 @@ impl Tsffs {
         });
 
         let cmplog_enabled = self.cmplog;
+        let timeouts_are_solutions = self.timeouts_are_solutions;
         let corpus_directory = self.corpus_directory.clone();
         let solutions_directory = self.solutions_directory.clone();
+        let compress_artifacts = self.compress_artifacts;
         let executable_tokens = self
             .token_executables
             .iter()
@@ -184,6 +208,8 @@ impl Tsffs {
         let generate_random_corpus = self.generate_random_corpus;
         let initial_random_corpus_size = self.initial_random_corpus_size;
         let debug_log_libafl = self.debug_log_libafl;
+        let max_testcase_size = self.max_testcase_size;
+        let debug_log_fuzzer_channel = self.debug_log_fuzzer_channel;
         let initial_contents = self
             .use_initial_as_corpus
             .then(|| {
@@ -197,7 +223,7 @@ impl Tsffs {
         // which prevents forward progress.
         self.fuzz_thread
             .set(spawn(move || -> Result<()> {
-                if debug_log_libafl {
+                if debug_log_libafl || debug_log_fuzzer_channel {
                     let reg = registry().with({
                         fmt::layer()
                             .compact()
@@ -205,10 +231,15 @@ impl Tsffs {
                             .with_thread_names(true)
                             .with_writer(stderr)
                             .with_filter(LevelFilter::TRACE)
-                            .with_filter(filter_fn(|metadata| {
+                            .with_filter(filter_fn(move |metadata| {
+                                if metadata.target() == FUZZER_CHANNEL_LOG_TARGET {
+                                    return debug_log_fuzzer_channel;
+                                }
+
                                 // LLMP absolutely spams the log when tracing
-                                !(metadata.target() == "libafl_bolts::llmp"
-                                    && matches!(metadata.level(), &Level::TRACE))
+                                debug_log_libafl
+                                    && !(metadata.target() == "libafl_bolts::llmp"
+                                        && matches!(metadata.level(), &Level::TRACE))
                             }))
                     });
 
@@ -222,6 +253,11 @@ impl Tsffs {
 
                 let mut harness = |input: &BytesInput| {
                     let testcase = BytesInput::new(input.target_bytes().as_slice().to_vec());
+                    tracing::debug!(
+                        target: FUZZER_CHANNEL_LOG_TARGET,
+                        "Sending testcase ({} bytes, cmplog: false)",
+                        testcase.bytes().len()
+                    );
                     client
                         .borrow_mut()
                         .0
@@ -236,11 +272,21 @@ impl Tsffs {
                         Ok(m) => m,
                     };
 
+                    tracing::debug!(
+                        target: FUZZER_CHANNEL_LOG_TARGET,
+                        "Received exit kind {status:?}"
+                    );
+
                     status
                 };
 
                 let mut aflpp_cmp_harness = |input: &BytesInput| {
                     let testcase = BytesInput::new(input.target_bytes().as_slice().to_vec());
+                    tracing::debug!(
+                        target: FUZZER_CHANNEL_LOG_TARGET,
+                        "Sending testcase ({} bytes, cmplog: true)",
+                        testcase.bytes().len()
+                    );
                     client
                         .borrow_mut()
                         .0
@@ -255,6 +301,11 @@ impl Tsffs {
                         Ok(m) => m,
                     };
 
+                    tracing::debug!(
+                        target: FUZZER_CHANNEL_LOG_TARGET,
+                        "Received exit kind {status:?}"
+                    );
+
                     status
                 };
 
@@ -286,10 +337,18 @@ impl Tsffs {
 
                 let crash_feedback = CrashFeedback::new();
                 let timeout_feedback = TimeFeedback::new(Self::TIMEOUT_FEEDBACK_NAME);
+                let timeout_solution_feedback = feedback_and_fast!(
+                    ConstFeedback::new(timeouts_are_solutions),
+                    TimeoutFeedback::new()
+                );
 
                 let solutions = OnDiskCorpus::with_meta_format(
                     solutions_directory.clone(),
-                    OnDiskMetadataFormat::JsonPretty,
+                    if compress_artifacts {
+                        OnDiskMetadataFormat::JsonGzip
+                    } else {
+                        OnDiskMetadataFormat::JsonPretty
+                    },
                 )
                 .map_err(|e| {
                     eprintln!("Failed to initialize solutions corpus: {e}");
@@ -299,7 +358,11 @@ impl Tsffs {
                 let corpus = CachedOnDiskCorpus::with_meta_format(
                     corpus_directory.clone(),
                     Self::CORPUS_CACHE_SIZE,
-                    Some(OnDiskMetadataFormat::Json),
+                    Some(if compress_artifacts {
+                        OnDiskMetadataFormat::JsonGzip
+                    } else {
+                        OnDiskMetadataFormat::Json
+                    }),
                 )
                 .map_err(|e| {
                     eprintln!("Failed to initialize corpus: {e}");
@@ -312,7 +375,8 @@ impl Tsffs {
                 let generalization_stage = GeneralizationStage::new(&edges_observer);
 
                 let mut feedback = feedback_or!(map_feedback, time_feedback);
-                let mut objective = feedback_or_fast!(crash_feedback, timeout_feedback);
+                let mut objective =
+                    feedback_or_fast!(crash_feedback, timeout_feedback, timeout_solution_feedback);
 
                 let mut state = StdState::new(
                     StdRand::with_seed(current_nanos()),
@@ -334,6 +398,10 @@ impl Tsffs {
 
                 state.add_metadata(tokens);
 
+                if max_testcase_size != 0 {
+                    state.set_max_size(max_testcase_size);
+                }
+
                 let scheduler =
                     IndexesLenTimeMinimizerScheduler::new(StdWeightedScheduler::with_schedule(
                         &mut state,
@@ -551,10 +619,14 @@ impl Tsffs {
             }))
             .map_err(|_| anyhow!("Fuzzer thread already set"))?;
 
+        self.start_watchdog_thread()?;
+
         Ok(())
     }
 
     pub fn send_shutdown(&mut self) -> Result<()> {
+        self.disable_watchdog();
+
         if let Some(stx) = self.fuzzer_shutdown.get_mut() {
             stx.send(ShutdownMessage::default())?;
         }
@@ -562,7 +634,51 @@ impl Tsffs {
         Ok(())
     }
 
+    /// Shut down the fuzzer thread and wait for it to exit, releasing its message channels so
+    /// the fuzzer thread can be started again. Testcases the fuzzer requests before it
+    /// checks for the shutdown message are reported to it as executed without being run.
+    pub fn stop_fuzzer_thread(&mut self) -> Result<()> {
+        self.send_shutdown()?;
+
+        let Some(fuzz_thread) = self.fuzz_thread.take() else {
+            return Ok(());
+        };
+
+        if let (Some(tx), Some(rx)) = (self.fuzzer_tx.take(), self.fuzzer_rx.take()) {
+            // NOTE: The fuzzer may be waiting for the exit kind of the current testcase. If it
+            // is not, the extra exit kind is never received and is dropped with the channel.
+            let _ = tx.send(ExitKind::Ok);
+
+            while !fuzz_thread.is_finished() {
+                match rx.recv_timeout(Duration::from_millis(100)) {
+                    Ok(_) => {
+                        let _ = tx.send(ExitKind::Ok);
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        }
+
+        self.fuzzer_shutdown.take();
+        self.fuzzer_messages.take();
+
+        if let Err(e) = fuzz_thread
+            .join()
+            .map_err(|_| anyhow!("Fuzzer thread panicked"))?
+        {
+            warn!(
+                self.as_conf_object(),
+                "Fuzzer thread exited with error: {e}"
+            );
+        }
+
+        Ok(())
+    }
+
     pub fn get_testcase(&mut self) -> Result<Testcase> {
+        self.feed_watchdog();
+
         let testcase = if let Some(testcase) = self.repro_testcase.as_ref() {
             debug!(self.as_conf_object(), "Using repro testcase");
             Testcase {
@@ -7,9 +7,10 @@ use std::time::SystemTime;
 
 use crate::{
     arch::ArchitectureOperations,
+    log::LogMessage,
     magic::MagicNumber,
     os::DebugInfoConfig,
-    state::{SolutionKind, StopReason},
+    state::{ExceptionAction, RepeatedStartAction, SolutionKind, StopReason},
     ManualStartInfo, Tsffs,
 };
 use anyhow::{anyhow, bail, Result};
@@ -19,34 +20,60 @@ use simics::{
         continue_simulation, log_level, object_is_processor, quit, run_alone, set_log_level,
         AsConfObject, ConfObject, GenericTransaction, LogLevel,
     },
-    debug, get_processor_number, info, trace, warn,
+    debug, error, get_processor_number, info, trace, warn,
 };
 
 impl Tsffs {
+    /// Check whether the configured time limit has been reached at the end of an iteration.
+    /// If it has, the fuzzer is shut down and SIMICS exits if `quit_on_iteration_limit` is
+    /// set. Returns whether the limit was reached, in which case the simulation should be
+    /// left stopped.
+    fn time_limit_reached(&mut self) -> Result<bool> {
+        if self.time_limit == 0 {
+            return Ok(false);
+        }
+
+        let duration = SystemTime::now().duration_since(
+            *self
+                .start_time
+                .get()
+                .ok_or_else(|| anyhow!("Start time was not set"))?,
+        )?;
+
+        if duration.as_secs() < self.time_limit {
+            return Ok(false);
+        }
+
+        // Set the log level so this message always prints
+        set_log_level(self.as_conf_object_mut(), LogLevel::Info)?;
+
+        info!(
+            self.as_conf_object(),
+            "Configured time limit of {} seconds reached. Stopping after {} iterations ({} exec/s).",
+            self.time_limit,
+            self.iterations,
+            self.iterations as f32 / duration.as_secs_f32()
+        );
+
+        self.send_shutdown()?;
+
+        if self.quit_on_iteration_limit {
+            quit(0)?;
+        }
+
+        Ok(true)
+    }
+
     fn on_simulation_stopped_magic_start(&mut self, magic_number: MagicNumber) -> Result<()> {
         if !self.have_initial_snapshot() {
             self.start_fuzzer_thread()?;
 
-            let start_processor = self
+            let start_processor_raw = self
                 .start_processor()
-                .ok_or_else(|| anyhow!("No start processor"))?;
-            let start_processor_raw = start_processor.cpu();
-
-            let start_info = match magic_number {
-                MagicNumber::StartBufferPtrSizePtr => {
-                    start_processor.get_magic_start_buffer_ptr_size_ptr()?
-                }
-                MagicNumber::StartBufferPtrSizeVal => {
-                    start_processor.get_magic_start_buffer_ptr_size_val()?
-                }
-                MagicNumber::StartBufferPtrSizePtrVal => {
-                    start_processor.get_magic_start_buffer_ptr_size_ptr_val()?
-                }
-                MagicNumber::StopNormal => unreachable!("StopNormal is not handled here"),
-                MagicNumber::StopAssert => unreachable!("StopAssert is not handled here"),
-            };
+                .ok_or_else(|| anyhow!("No start processor"))?
+                .cpu();
 
-            debug!(self.as_conf_object(), "Start info: {start_info:?}");
+            let start_info = self.magic_start_info(magic_number)?;
 
             self.start_info
                 .set(start_info)
@@ -72,6 +99,65 @@ impl Tsffs {
             }
             self.get_and_write_testcase()?;
             self.post_timeout_event()?;
+        } else if self.rediscovering_buffer() && self.buffer_rediscovery_pending {
+            // The testcase was not written when the last execution stopped, because the
+            // buffer may have moved since it was found
+            self.buffer_rediscovery_pending = false;
+
+            let start_info = self.magic_start_info(magic_number)?;
+
+            self.start_info.take();
+            self.start_info
+                .set(start_info)
+                .map_err(|_| anyhow!("Failed to set start size"))?;
+
+            self.get_and_write_testcase()?;
+            self.post_timeout_event()?;
+        } else if self.restore_snapshot_on_stop {
+            // The initial snapshot is taken at the start harness, so reaching it again means
+            // the target looped back to it during this testcase execution
+            match self.repeated_start_action {
+                RepeatedStartAction::Resume => {
+                    debug!(
+                        self.as_conf_object(),
+                        "Start harness reached again, resuming"
+                    );
+                }
+                RepeatedStartAction::Error => {
+                    // Set the log level so this message always prints
+                    set_log_level(self.as_conf_object_mut(), LogLevel::Info)?;
+
+                    error!(
+                        self.as_conf_object(),
+                        "Start harness reached again during a testcase execution. Stopping fuzzing and leaving the simulation stopped."
+                    );
+
+                    self.cancel_timeout_event()?;
+                    self.send_shutdown()?;
+
+                    return Ok(());
+                }
+                RepeatedStartAction::Resnapshot => {
+                    info!(
+                        self.as_conf_object(),
+                        "Start harness reached again, stopping normally and replacing the initial snapshot"
+                    );
+
+                    // The buffer may have moved since the initial start harness, so it is
+                    // found again for the new snapshot
+                    self.repeated_start_info = Some(self.magic_start_info(magic_number)?);
+
+                    return self.on_simulation_stopped_magic_stop();
+                }
+                RepeatedStartAction::Stop => {
+                    debug!(
+                        self.as_conf_object(),
+                        "Start harness reached again, stopping normally"
+                    );
+
+                    return self.on_simulation_stopped_magic_stop();
+                }
+            }
         }
 
         self.execution_trace.0.clear();
@@ -92,6 +178,8 @@ impl Tsffs {
     }
 
     fn on_simulation_stopped_magic_stop(&mut self) -> Result<()> {
+        let repeated_start_info = self.repeated_start_info.take();
+
         if !self.have_initial_snapshot() {
             warn!(
                 self.as_conf_object(),
@@ -99,9 +187,13 @@ impl Tsffs {
             );
         } else {
             self.cancel_timeout_event()?;
+            self.log_execution_time("Ok")?;
+            self.run_post_testcase_commands()?;
 
             if self.repro_bookmark_set {
                 self.stopped_for_repro = true;
+                // The simulation is left stopped at the end of the repro execution
+                self.disable_watchdog();
                 let current_log_level = log_level(self.as_conf_object_mut())?;
 
                 if current_log_level < LogLevel::Info as u32 {
@@ -147,26 +239,59 @@ impl Tsffs {
                 }
             }
 
-            let fuzzer_tx = self
-                .fuzzer_tx
-                .get()
-                .ok_or_else(|| anyhow!("No fuzzer tx channel"))?;
+            if self.time_limit_reached()? {
+                return Ok(());
+            }
 
-            fuzzer_tx.send(ExitKind::Ok)?;
+            if !self.apply_coverage_baseline()? {
+                let fuzzer_tx = self
+                    .fuzzer_tx
+                    .get()
+                    .ok_or_else(|| anyhow!("No fuzzer tx channel"))?;
+
+                fuzzer_tx.send(ExitKind::Ok)?;
+            }
+
+            let resnapshot = repeated_start_info.is_some();
+
+            if let Some(start_info) = repeated_start_info {
+                // The next testcase runs from a new initial snapshot at the repeated start
+                // harness instead of from the restored initial snapshot
+                self.refresh_initial_snapshot()?;
+                self.record_tracked_attributes()?;
+                self.start_info.take();
+                self.start_info
+                    .set(start_info)
+                    .map_err(|_| anyhow!("Failed to set start size"))?;
+            } else if self.restore_snapshot_on_stop {
+                self.restore_initial_snapshot()?;
+                self.refresh_initial_snapshot_if_needed()?;
+                self.check_tracked_attributes()?;
+            }
 
-            self.restore_initial_snapshot()?;
             self.coverage_prev_loc = 0;
 
-            if self.start_info.get().is_some() {
-                self.get_and_write_testcase()?;
-            } else {
+            // After replacing the snapshot, execution resumes at the start harness where the
+            // buffer was just found again, so the testcase is written now
+            if self.rediscovering_buffer() && !resnapshot {
                 debug!(
                     self.as_conf_object(),
-                    "Missing start buffer or size, not writing testcase."
+                    "Rediscovering buffer, writing testcase when the start harness is reached"
                 );
-            }
 
-            self.post_timeout_event()?;
+                self.buffer_rediscovery_pending = true;
+            } else {
+                if self.start_info.get().is_some() {
+                    self.get_and_write_testcase()?;
+                } else {
+                    debug!(
+                        self.as_conf_object(),
+                        "Missing start buffer or size, not writing testcase."
+                    );
+                }
+
+                self.post_timeout_event()?;
+            }
         }
 
         if self.save_all_execution_traces {
@@ -215,6 +340,8 @@ impl Tsffs {
                 .ok_or_else(|| anyhow!("No start processor"))?
                 .get_manual_start_info(&info)?;
 
+            let start_info = self.align_start_info(start_info)?;
+
             self.start_info
                 .set(start_info)
                 .map_err(|_| anyhow!("Failed to set start info"))?;
@@ -310,9 +437,13 @@ impl Tsffs {
             );
         } else {
             self.cancel_timeout_event()?;
+            self.log_execution_time("Ok")?;
+            self.run_post_testcase_commands()?;
 
             if self.repro_bookmark_set {
                 self.stopped_for_repro = true;
+                // The simulation is left stopped at the end of the repro execution
+                self.disable_watchdog();
                 let current_log_level = log_level(self.as_conf_object_mut())?;
 
                 if current_log_level < LogLevel::Info as u32 {
@@ -358,14 +489,25 @@ impl Tsffs {
                 }
             }
 
-            let fuzzer_tx = self
-                .fuzzer_tx
-                .get()
-                .ok_or_else(|| anyhow!("No fuzzer tx channel"))?;
+            if self.time_limit_reached()? {
+                return Ok(());
+            }
 
-            fuzzer_tx.send(ExitKind::Ok)?;
+            if !self.apply_coverage_baseline()? {
+                let fuzzer_tx = self
+                    .fuzzer_tx
+                    .get()
+                    .ok_or_else(|| anyhow!("No fuzzer tx channel"))?;
+
+                fuzzer_tx.send(ExitKind::Ok)?;
+            }
+
+            if self.restore_snapshot_on_stop {
+                self.restore_initial_snapshot()?;
+                self.refresh_initial_snapshot_if_needed()?;
+                self.check_tracked_attributes()?;
+            }
 
-            self.restore_initial_snapshot()?;
             self.coverage_prev_loc = 0;
 
             if self.start_info.get().is_some() {
@@ -406,9 +548,14 @@ impl Tsffs {
             );
         } else {
             self.cancel_timeout_event()?;
+            self.log_execution_time(&format!("{kind:?}"))?;
+            self.log_solution_context(&kind)?;
+            self.run_post_testcase_commands()?;
 
             if self.repro_bookmark_set {
                 self.stopped_for_repro = true;
+                // The simulation is left stopped at the end of the repro execution
+                self.disable_watchdog();
                 let current_log_level = log_level(self.as_conf_object_mut())?;
 
                 if current_log_level < LogLevel::Info as u32 {
@@ -454,23 +601,58 @@ impl Tsffs {
                 }
             }
 
-            let fuzzer_tx = self
-                .fuzzer_tx
-                .get()
-                .ok_or_else(|| anyhow!("No fuzzer tx channel"))?;
+            if self.time_limit_reached()? {
+                return Ok(());
+            }
 
-            match kind {
-                SolutionKind::Timeout => {
-                    self.timeouts += 1;
-                    fuzzer_tx.send(ExitKind::Timeout)?
+            if self.apply_coverage_baseline()? {
+                warn!(
+                    self.as_conf_object(),
+                    "Coverage baseline execution with an empty testcase stopped with solution {kind:?}"
+                );
+            } else {
+                let fuzzer_tx = self
+                    .fuzzer_tx
+                    .get()
+                    .ok_or_else(|| anyhow!("No fuzzer tx channel"))?;
+
+                match kind {
+                    SolutionKind::Timeout => {
+                        self.timeouts += 1;
+                        fuzzer_tx.send(ExitKind::Timeout)?
+                    }
+                    SolutionKind::Exception
+                    | SolutionKind::StackOverflow
+                    | SolutionKind::Breakpoint
+                    | SolutionKind::Manual => {
+                        self.solutions += 1;
+                        fuzzer_tx.send(ExitKind::Crash)?
+                    }
                 }
-                SolutionKind::Exception | SolutionKind::Breakpoint | SolutionKind::Manual => {
-                    self.solutions += 1;
-                    fuzzer_tx.send(ExitKind::Crash)?
+
+                if self.stop_on_solution
+                    && (!matches!(kind, SolutionKind::Timeout) || self.timeouts_are_solutions)
+                {
+                    // Set the log level so this message always prints
+                    set_log_level(self.as_conf_object_mut(), LogLevel::Info)?;
+
+                    info!(
+                        self.as_conf_object(),
+                        "Stopped on solution {kind:?} after {} iterations. The simulation is left stopped at the solution.",
+                        self.iterations
+                    );
+
+                    self.send_shutdown()?;
+
+                    return Ok(());
                 }
             }
 
+            // NOTE: Solutions always restore the initial snapshot, which is past the start
+            // harness, so the testcase is written now even when rediscovering the buffer
             self.restore_initial_snapshot()?;
+            self.refresh_initial_snapshot_if_needed()?;
+            self.check_tracked_attributes()?;
             self.coverage_prev_loc = 0;
 
             if self.start_info.get().is_some() {
@@ -503,12 +685,91 @@ impl Tsffs {
         Ok(())
     }
 
+    /// Handle a stop while probing the harness. Starts are recorded and execution resumes
+    /// without writing a testcase, and the first stop after a start is logged and leaves the
+    /// simulation stopped.
+    fn on_simulation_stopped_probe(&mut self, reason: StopReason) -> Result<()> {
+        match reason {
+            StopReason::Magic {
+                magic_number:
+                    MagicNumber::StartBufferPtrSizePtr
+                    | MagicNumber::StartBufferPtrSizeVal
+                    | MagicNumber::StartBufferPtrSizePtrVal,
+            }
+            | StopReason::ManualStart { .. }
+            | StopReason::ManualStartWithoutBuffer { .. } => {
+                if let StopReason::ManualStart { processor, .. }
+                | StopReason::ManualStartWithoutBuffer { processor } = reason
+                {
+                    self.add_processor(processor, true)?;
+                }
+
+                let start_cycle = self
+                    .start_processor()
+                    .ok_or_else(|| anyhow!("No start processor"))?
+                    .cycle()
+                    .get_cycle_count()?;
+
+                info!(
+                    self.as_conf_object(),
+                    "Probe: start harness reached at cycle {start_cycle}"
+                );
+
+                if self.probe_start_cycle.is_none() {
+                    self.probe_start_cycle = Some(start_cycle);
+                }
+
+                run_alone(|| {
+                    continue_simulation(0)?;
+                    Ok(())
+                })?;
+            }
+            _ => {
+                // The simulation is left stopped at the first stop after the start harness
+                self.disable_watchdog();
+
+                let stop_cycle = self
+                    .start_processor()
+                    .map(|p| p.cycle().get_cycle_count())
+                    .transpose()?;
+
+                // Set the log level so this message always prints
+                set_log_level(self.as_conf_object_mut(), LogLevel::Info)?;
+
+                if let Some(start_cycle) = self.probe_start_cycle {
+                    info!(
+                        self.as_conf_object(),
+                        "Probe: {reason:?} reached at cycle {} after start at cycle {start_cycle}",
+                        stop_cycle.unwrap_or_default()
+                    );
+                } else {
+                    warn!(
+                        self.as_conf_object(),
+                        "Probe: {reason:?} reached before the start harness"
+                    );
+                }
+
+                self.log(LogMessage::probe(
+                    self.probe_start_cycle,
+                    stop_cycle,
+                    format!("{reason:?}"),
+                ))?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn on_simulation_stopped_with_reason(&mut self, reason: StopReason) -> Result<()> {
         debug!(
             self.as_conf_object(),
             "Simulation stopped with reason {reason:?}"
         );
 
+        if self.probe_harness {
+            return self.on_simulation_stopped_probe(reason);
+        }
+
         match reason {
             StopReason::Magic { magic_number } => {
                 self.on_simulation_stopped_with_magic(magic_number)
@@ -521,7 +782,65 @@ impl Tsffs {
             }
             StopReason::ManualStop => self.on_simulation_stopped_manual_stop(),
             StopReason::Solution { kind } => self.on_simulation_stopped_solution(kind),
+            StopReason::Timeout => self.on_simulation_stopped_solution(SolutionKind::Timeout),
+            StopReason::Halt => self.on_simulation_stopped_halt(),
+        }
+    }
+
+    fn on_simulation_stopped_halt(&mut self) -> Result<()> {
+        if !self.have_initial_snapshot() {
+            warn!(
+                self.as_conf_object(),
+                "Halted before start was reached (no snapshot). Not resuming."
+            );
+            return Ok(());
+        }
+
+        // The current iteration is finished as a normal exit, in the same way as a magic stop
+        self.cancel_timeout_event()?;
+        self.log_execution_time("Ok")?;
+        self.run_post_testcase_commands()?;
+
+        self.iterations += 1;
+
+        if !self.apply_coverage_baseline()? {
+            let fuzzer_tx = self
+                .fuzzer_tx
+                .get()
+                .ok_or_else(|| anyhow!("No fuzzer tx channel"))?;
+
+            fuzzer_tx.send(ExitKind::Ok)?;
+        }
+
+        if self.save_all_execution_traces {
+            self.save_execution_trace()?;
+        }
+
+        if self.symbolic_coverage {
+            self.save_symbolic_coverage()?;
         }
+
+        let duration = SystemTime::now().duration_since(
+            *self
+                .start_time
+                .get()
+                .ok_or_else(|| anyhow!("Start time was not set"))?,
+        )?;
+
+        // Set the log level so this message always prints
+        set_log_level(self.as_conf_object_mut(), LogLevel::Info)?;
+
+        info!(
+            self.as_conf_object(),
+            "Halted after {} iterations in {} seconds ({} exec/s).",
+            self.iterations,
+            duration.as_secs_f32(),
+            self.iterations as f32 / duration.as_secs_f32()
+        );
+
+        self.send_shutdown()?;
+
+        Ok(())
     }
 
     fn on_simulation_stopped_without_reason(&mut self) -> Result<()> {
@@ -542,6 +861,8 @@ impl Tsffs {
                 "Simulation stopped without reason, not resuming."
             );
 
+            self.disable_watchdog();
+
             let duration = SystemTime::now().duration_since(
                 *self
                     .start_time
@@ -578,21 +899,59 @@ impl Tsffs {
         //  Log information from the fuzzer
         self.log_messages()?;
 
-        if let Some(reason) = self.stop_reason.take() {
+        // NOTE: Exceptions and breakpoints which occur while a stop is handled (for example,
+        // while the initial snapshot is restored or a testcase is written) are not caused by
+        // the testcase, so they are ignored instead of replacing the stop reason
+        self.handling_stop = true;
+
+        let result = if let Some(reason) = self.stop_reason.take() {
             self.on_simulation_stopped_with_reason(reason)
         } else {
             self.on_simulation_stopped_without_reason()
-        }
+        };
+
+        self.handling_stop = false;
+
+        result
     }
 
-    /// Called on core exception HAP. Check to see if this exception is configured as a solution
-    /// or all exceptions are solutions and trigger a stop if so
-    pub fn on_exception(&mut self, _obj: *mut ConfObject, exception: i64) -> Result<()> {
-        if self.all_exceptions_are_solutions || self.exceptions.contains(&exception) {
-            self.stop_simulation(StopReason::Solution {
-                kind: SolutionKind::Exception,
-            })?;
+    /// Called on core exception HAP. Check to see if an action is configured for this
+    /// exception, or if it is configured as a solution or all exceptions are solutions, and
+    /// trigger a stop if so
+    pub fn on_exception(&mut self, obj: *mut ConfObject, exception: i64) -> Result<()> {
+        if self.handling_stop {
+            debug!(
+                self.as_conf_object(),
+                "Ignoring exception {exception} which occurred while handling a stop"
+            );
+            return Ok(());
         }
+
+        let action = self.exception_actions.get(&exception).copied().or_else(|| {
+            (self.all_exceptions_are_solutions || self.exceptions.contains(&exception))
+                .then_some(ExceptionAction::Solution)
+        });
+
+        match action {
+            Some(ExceptionAction::Solution) => {
+                let kind = if self.is_stack_overflow(obj, exception)? {
+                    SolutionKind::StackOverflow
+                } else {
+                    SolutionKind::Exception
+                };
+
+                if self.exception_grace_period > 0.0 && self.pending_fault.is_none() {
+                    self.start_grace_period(kind)?;
+                } else {
+                    self.stop_simulation(StopReason::Solution { kind })?;
+                }
+            }
+            Some(ExceptionAction::Stop) => {
+                self.stop_simulation(StopReason::ManualStop)?;
+            }
+            Some(ExceptionAction::Ignore) | None => {}
+        }
+
         Ok(())
     }
 
@@ -604,7 +963,24 @@ impl Tsffs {
         breakpoint: i64,
         transaction: *mut GenericTransaction,
     ) -> Result<()> {
-        if self.all_breakpoints_are_solutions || self.breakpoints.contains(&(breakpoint as i32)) {
+        if self.handling_stop {
+            debug!(
+                self.as_conf_object(),
+                "Ignoring breakpoint {breakpoint} which was hit while handling a stop"
+            );
+            return Ok(());
+        }
+
+        if self.stop_breakpoints.contains(&(breakpoint as i32)) {
+            debug!(
+                self.as_conf_object(),
+                "Stop breakpoint {breakpoint} hit, stopping normally"
+            );
+
+            self.stop_simulation(StopReason::ManualStop)?;
+        } else if self.all_breakpoints_are_solutions
+            || self.breakpoints.contains(&(breakpoint as i32))
+        {
             info!(
                 self.as_conf_object(),
                 "on_breakpoint_memop({:#x}, {}, {:#x})",
@@ -646,6 +1022,14 @@ impl Tsffs {
 
             let index_selector = processor.get_magic_index_selector()?;
 
+            if self.ignore_magic_in_buffer && self.magic_in_buffer(processor_number)? {
+                debug!(
+                    self.as_conf_object(),
+                    "Ignoring magic instruction {magic_number} executed from the testcase buffer"
+                );
+                return Ok(());
+            }
+
             if match magic_number {
                 MagicNumber::StartBufferPtrSizePtr
                 | MagicNumber::StartBufferPtrSizeVal
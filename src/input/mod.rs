@@ -0,0 +1,380 @@
+// Copyright (C) 2024 Intel Corporation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Transformations applied to testcases before they are written to the target
+
+use anyhow::{anyhow, bail, ensure, Error, Result};
+use crc32fast::hash;
+use serde::{Deserialize, Serialize};
+use std::{mem::size_of, str::FromStr};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+/// A transformation applied to each testcase before it is written to the target, for targets
+/// which only accept inputs wrapped with a length or checksum
+pub(crate) enum InputTransform {
+    /// Prepend the length of the input as a little-endian integer of the given width in bytes
+    LengthPrefixLe(usize),
+    /// Prepend the length of the input as a big-endian integer of the given width in bytes
+    LengthPrefixBe(usize),
+    /// Append the CRC32 of the input as a little-endian 32-bit integer
+    Crc32Le,
+    /// Append the CRC32 of the input as a big-endian 32-bit integer
+    Crc32Be,
+    /// Append the sum of the bytes of the input, modulo 256, as a single byte
+    Sum8,
+}
+
+impl FromStr for InputTransform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "length-le8" => Self::LengthPrefixLe(1),
+            "length-le16" => Self::LengthPrefixLe(2),
+            "length-le32" => Self::LengthPrefixLe(4),
+            "length-le64" => Self::LengthPrefixLe(8),
+            "length-be16" => Self::LengthPrefixBe(2),
+            "length-be32" => Self::LengthPrefixBe(4),
+            "length-be64" => Self::LengthPrefixBe(8),
+            "crc32-le" => Self::Crc32Le,
+            "crc32-be" => Self::Crc32Be,
+            "sum8" => Self::Sum8,
+            _ => bail!("Unknown input transform: {}", s),
+        })
+    }
+}
+
+impl InputTransform {
+    /// The number of bytes this transform adds to the input
+    pub fn overhead(&self) -> usize {
+        match self {
+            InputTransform::LengthPrefixLe(width) | InputTransform::LengthPrefixBe(width) => *width,
+            InputTransform::Crc32Le | InputTransform::Crc32Be => 4,
+            InputTransform::Sum8 => 1,
+        }
+    }
+
+    /// The maximum length of input this transform can be applied to, if it is limited
+    pub fn maximum_input_length(&self) -> Option<usize> {
+        match self {
+            InputTransform::LengthPrefixLe(width) | InputTransform::LengthPrefixBe(width)
+                if *width < size_of::<u64>() =>
+            {
+                Some((1 << (width * 8)) - 1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Apply this transform to an input, returning the transformed input
+    pub fn apply(&self, input: &[u8]) -> Result<Vec<u8>> {
+        Ok(match self {
+            InputTransform::LengthPrefixLe(width) | InputTransform::LengthPrefixBe(width) => {
+                let length = input.len() as u64;
+
+                ensure!(
+                    *width >= 8 || length < 1 << (width * 8),
+                    "Input length {length} does not fit in a {width} byte length prefix"
+                );
+
+                let prefix = if matches!(self, InputTransform::LengthPrefixLe(_)) {
+                    length.to_le_bytes()[..*width].to_vec()
+                } else {
+                    length.to_be_bytes()[8 - width..].to_vec()
+                };
+
+                prefix.into_iter().chain(input.iter().copied()).collect()
+            }
+            InputTransform::Crc32Le => [input, &hash(input).to_le_bytes()].concat(),
+            InputTransform::Crc32Be => [input, &hash(input).to_be_bytes()].concat(),
+            InputTransform::Sum8 => {
+                let sum = input.iter().fold(0u8, |s, b| s.wrapping_add(*b));
+                [input, &[sum]].concat()
+            }
+        })
+    }
+}
+
+/// Apply a sequence of transforms to an input in order, each transform applying to the output
+/// of the previous transform. The input is first truncated so that the transformed input is
+/// no larger than `maximum_size`, which ensures lengths and checksums are never truncated
+/// when the input is written, and so that its length fits in every length prefix.
+pub(crate) fn apply_input_transforms(
+    transforms: &[InputTransform],
+    input: &[u8],
+    maximum_size: usize,
+) -> Result<Vec<u8>> {
+    let overhead = transforms.iter().map(|t| t.overhead()).sum::<usize>();
+    // Each transform is applied to the input with the overhead of the transforms before it
+    let maximum_length = transforms
+        .iter()
+        .scan(0, |preceding_overhead, transform| {
+            let limit = transform
+                .maximum_input_length()
+                .map(|length| length.saturating_sub(*preceding_overhead));
+            *preceding_overhead += transform.overhead();
+            Some(limit)
+        })
+        .flatten()
+        .fold(maximum_size.saturating_sub(overhead), usize::min);
+    let input = &input[..input.len().min(maximum_length)];
+
+    transforms
+        .iter()
+        .try_fold(input.to_vec(), |input, transform| transform.apply(&input))
+}
+
+/// Select the buffer a testcase is written to when `additional_buffers` input buffers are
+/// configured in addition to the buffer passed to the start harness. The first byte of the
+/// testcase selects the buffer, modulo the number of buffers, and the rest of the testcase is
+/// written to it. Index 0 is the buffer passed to the start harness and later indices are the
+/// additional buffers in the order they were added. Without additional buffers, the whole
+/// testcase is written to the start buffer.
+pub(crate) fn select_input_buffer(additional_buffers: usize, testcase: &[u8]) -> (usize, &[u8]) {
+    if additional_buffers == 0 {
+        return (0, testcase);
+    }
+
+    match testcase.split_first() {
+        Some((selector, rest)) => (*selector as usize % (additional_buffers + 1), rest),
+        None => (0, testcase),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+/// How the part of the testcase buffer after the end of each testcase is filled, for targets
+/// which read the whole buffer regardless of the size of the input
+pub(crate) enum BufferFill {
+    #[default]
+    /// Leave the rest of the buffer untouched
+    None,
+    /// Fill the rest of the buffer with zero bytes
+    Zero,
+    /// Fill the rest of the buffer with repeated copies of the input
+    Repeat,
+    /// Fill the rest of the buffer with the given byte
+    Byte(u8),
+}
+
+impl FromStr for BufferFill {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "none" => Self::None,
+            "zero" => Self::Zero,
+            "repeat" => Self::Repeat,
+            _ => {
+                let Some(byte) = s.strip_prefix("byte:") else {
+                    bail!("Unknown buffer fill: {}", s);
+                };
+
+                let byte = if let Some(hex) = byte.strip_prefix("0x") {
+                    u8::from_str_radix(hex, 16)
+                } else {
+                    byte.parse()
+                };
+
+                Self::Byte(byte.map_err(|e| anyhow!("Invalid buffer fill byte in {s}: {e}"))?)
+            }
+        })
+    }
+}
+
+impl BufferFill {
+    /// The bytes to write after an input of `input` to fill a buffer of `maximum_size` bytes,
+    /// or `None` if the rest of the buffer is left untouched
+    pub fn fill(&self, input: &[u8], maximum_size: usize) -> Option<Vec<u8>> {
+        let remaining = maximum_size.saturating_sub(input.len());
+
+        match self {
+            BufferFill::None => None,
+            BufferFill::Zero => Some(vec![0; remaining]),
+            BufferFill::Byte(byte) => Some(vec![*byte; remaining]),
+            // An empty input has nothing to repeat, so the buffer is left untouched
+            BufferFill::Repeat if input.is_empty() => None,
+            BufferFill::Repeat => Some(input.iter().copied().cycle().take(remaining).collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{apply_input_transforms, select_input_buffer, BufferFill, InputTransform};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_parse_input_transforms() {
+        assert_eq!(
+            InputTransform::from_str("length-le8").unwrap(),
+            InputTransform::LengthPrefixLe(1)
+        );
+        assert_eq!(
+            InputTransform::from_str("length-be32").unwrap(),
+            InputTransform::LengthPrefixBe(4)
+        );
+        assert_eq!(
+            InputTransform::from_str("crc32-le").unwrap(),
+            InputTransform::Crc32Le
+        );
+        assert_eq!(
+            InputTransform::from_str("crc32-be").unwrap(),
+            InputTransform::Crc32Be
+        );
+        assert_eq!(
+            InputTransform::from_str("sum8").unwrap(),
+            InputTransform::Sum8
+        );
+        assert!(InputTransform::from_str("length-be8").is_err());
+        assert!(InputTransform::from_str("crc32").is_err());
+        assert!(InputTransform::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_parse_buffer_fill() {
+        assert_eq!(BufferFill::from_str("none").unwrap(), BufferFill::None);
+        assert_eq!(BufferFill::from_str("zero").unwrap(), BufferFill::Zero);
+        assert_eq!(BufferFill::from_str("repeat").unwrap(), BufferFill::Repeat);
+        assert_eq!(
+            BufferFill::from_str("byte:65").unwrap(),
+            BufferFill::Byte(65)
+        );
+        assert_eq!(
+            BufferFill::from_str("byte:0xff").unwrap(),
+            BufferFill::Byte(0xff)
+        );
+        assert!(BufferFill::from_str("byte:256").is_err());
+        assert!(BufferFill::from_str("byte:0xzz").is_err());
+        assert!(BufferFill::from_str("ones").is_err());
+    }
+
+    #[test]
+    fn test_length_prefix() {
+        assert_eq!(
+            InputTransform::LengthPrefixLe(2).apply(b"abc").unwrap(),
+            b"\x03\x00abc"
+        );
+        assert_eq!(
+            InputTransform::LengthPrefixBe(4).apply(b"abc").unwrap(),
+            b"\x00\x00\x00\x03abc"
+        );
+        assert!(InputTransform::LengthPrefixLe(1).apply(&[0; 256]).is_err());
+    }
+
+    #[test]
+    fn test_checksums() {
+        // 0xcbf43926 is the standard CRC32 check value of "123456789"
+        assert_eq!(
+            InputTransform::Crc32Le.apply(b"123456789").unwrap(),
+            b"123456789\x26\x39\xf4\xcb"
+        );
+        assert_eq!(
+            InputTransform::Crc32Be.apply(b"123456789").unwrap(),
+            b"123456789\xcb\xf4\x39\x26"
+        );
+        assert_eq!(
+            InputTransform::Sum8.apply(&[0x80, 0x90, 0x01]).unwrap(),
+            [0x80, 0x90, 0x01, 0x11]
+        );
+        assert_eq!(InputTransform::Sum8.apply(&[]).unwrap(), [0x00]);
+    }
+
+    #[test]
+    fn test_maximum_input_length() {
+        assert_eq!(
+            InputTransform::LengthPrefixLe(1).maximum_input_length(),
+            Some(255)
+        );
+        assert_eq!(
+            InputTransform::LengthPrefixBe(2).maximum_input_length(),
+            Some(65535)
+        );
+        assert_eq!(
+            InputTransform::LengthPrefixLe(8).maximum_input_length(),
+            None
+        );
+        assert_eq!(InputTransform::Crc32Le.maximum_input_length(), None);
+        assert_eq!(InputTransform::Sum8.maximum_input_length(), None);
+    }
+
+    #[test]
+    fn test_stacked_transforms() {
+        // The checksum covers the input and the length prefix is applied to the checksummed
+        // input, so it includes the checksum byte
+        let transformed = apply_input_transforms(
+            &[InputTransform::Sum8, InputTransform::LengthPrefixBe(2)],
+            b"ab",
+            0x1000,
+        )
+        .unwrap();
+
+        assert_eq!(transformed, b"\x00\x03ab\xc3");
+    }
+
+    #[test]
+    fn test_transforms_truncate_to_maximum_size() {
+        let transformed = apply_input_transforms(
+            &[InputTransform::LengthPrefixLe(4), InputTransform::Crc32Le],
+            &[0x41; 16],
+            12,
+        )
+        .unwrap();
+
+        // The input is truncated so the length and checksum are written in full
+        assert_eq!(transformed.len(), 12);
+        assert_eq!(transformed[..4], [4, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_length_prefix_truncates_to_prefix_maximum() {
+        let input = vec![0x41; 300];
+        let transformed =
+            apply_input_transforms(&[InputTransform::LengthPrefixLe(1)], &input, 0x1000)
+                .expect("Failed to apply transforms");
+
+        assert_eq!(transformed.len(), 256);
+        assert_eq!(transformed[0], 255);
+        assert!(transformed[1..].iter().all(|b| *b == 0x41));
+    }
+
+    #[test]
+    fn test_stacked_length_prefix_counts_earlier_overhead() {
+        // The one byte prefix covers the input and its checksum, so the input is limited to
+        // 251 bytes
+        let transformed = apply_input_transforms(
+            &[InputTransform::Crc32Le, InputTransform::LengthPrefixLe(1)],
+            &[0x41; 300],
+            0x1000,
+        )
+        .unwrap();
+
+        assert_eq!(transformed.len(), 256);
+        assert_eq!(transformed[0], 255);
+    }
+
+    #[test]
+    fn test_buffer_fill() {
+        assert_eq!(BufferFill::None.fill(b"ab", 8), None);
+        assert_eq!(BufferFill::Zero.fill(b"ab", 5), Some(vec![0; 3]));
+        assert_eq!(BufferFill::Byte(0xcc).fill(b"ab", 4), Some(vec![0xcc; 2]));
+        assert_eq!(BufferFill::Repeat.fill(b"ab", 7), Some(b"ababa".to_vec()));
+        assert_eq!(BufferFill::Repeat.fill(b"", 4), None);
+        assert_eq!(BufferFill::Zero.fill(b"abcd", 2), Some(vec![]));
+    }
+
+    #[test]
+    fn test_select_input_buffer() {
+        // Without additional buffers the whole testcase goes to the start buffer
+        assert_eq!(select_input_buffer(0, b"\x01ab"), (0, &b"\x01ab"[..]));
+
+        // With two additional buffers, the first byte selects one of three buffers
+        assert_eq!(select_input_buffer(2, b"\x00ab"), (0, &b"ab"[..]));
+        assert_eq!(select_input_buffer(2, b"\x01ab"), (1, &b"ab"[..]));
+        assert_eq!(select_input_buffer(2, b"\x02ab"), (2, &b"ab"[..]));
+        assert_eq!(select_input_buffer(2, b"\x05ab"), (2, &b"ab"[..]));
+
+        // The selector byte is consumed, so a one byte testcase writes an empty input
+        assert_eq!(select_input_buffer(2, b"\x01"), (1, &b""[..]));
+        assert_eq!(select_input_buffer(2, b""), (0, &b""[..]));
+    }
+}
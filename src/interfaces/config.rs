@@ -1,15 +1,37 @@
 // Copyright (C) 2024 Intel Corporation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{arch::ArchitectureHint, Tsffs};
-use simics::{debug, get_processor_number, interface, AsConfObject, ConfObject, Result};
+use crate::{
+    arch::{ArchitectureHint, ExceptionPreset},
+    input::{BufferFill, InputTransform},
+    state::{ExceptionAction, RepeatedStartAction},
+    util::Utils,
+    BufferSymbol, FaultInjection, MemoryDumpRange, StackGuard, StartInfo, StartPhysicalAddress,
+    StartSize, TrackedAttribute, Tsffs,
+};
+use anyhow::anyhow;
+use simics::{
+    debug, get_object, get_processor_number, interface, object_is_processor, warn, AsConfObject,
+    AttrValue, ConfObject, GenericAddress, Result,
+};
 use std::{
     ffi::{c_char, CStr},
+    path::Path,
     str::FromStr,
 };
 
 #[interface(name = "config")]
 impl Tsffs {
+    /// Get the active configuration as a JSON string, to check that the configuration
+    /// intended was received. This includes the configuration set with the methods of this
+    /// interface, which cannot be read back as attributes, commonly checked attributes such
+    /// as the timeout, and the testcase buffer once the fuzzing loop has started.
+    pub fn get_configuration(&mut self) -> Result<AttrValue> {
+        debug!(self.as_conf_object(), "get_configuration()");
+
+        Ok(self.configuration_json()?.try_into()?)
+    }
+
     /// Add a processor to be traced. By default, only the processor the start event occurs on
     /// is used for tracing.
     pub fn add_trace_processor(&mut self, cpu: *mut ConfObject) -> Result<()> {
@@ -23,6 +45,48 @@ impl Tsffs {
         Ok(())
     }
 
+    /// Add a processor to be traced by the name of its object, for example
+    /// `qsp.mb.cpu0.core[0][1]`. This can be used in configuration where the processor
+    /// object itself is not available, and reports a clear error if no processor with the
+    /// name exists.
+    pub fn add_trace_processor_by_name(&mut self, name: *mut c_char) -> Result<()> {
+        let name = unsafe { CStr::from_ptr(name) }.to_str()?;
+        debug!(self.as_conf_object(), "add_trace_processor_by_name({name})");
+
+        let cpu = get_object(name).map_err(|e| anyhow!("No object named '{name}': {e}"))?;
+
+        if !object_is_processor(cpu)? {
+            return Err(anyhow!("Object '{name}' is not a processor").into());
+        }
+
+        self.add_processor(cpu, false)?;
+
+        Ok(())
+    }
+
+    /// Add all processors which are instances of the class `class_name` to be traced. This
+    /// can be used instead of `add_trace_processor` to trace all cores of a model without
+    /// naming each one.
+    pub fn add_trace_processors_of_class(&mut self, class_name: *mut c_char) -> Result<()> {
+        let class_name = unsafe { CStr::from_ptr(class_name) }.to_str()?;
+        debug!(
+            self.as_conf_object(),
+            "add_trace_processors_of_class({class_name})"
+        );
+
+        let processors = Utils::objects_of_class(class_name)?;
+
+        if processors.is_empty() {
+            return Err(anyhow!("No objects of class '{class_name}' exist").into());
+        }
+
+        processors
+            .into_iter()
+            .try_for_each(|cpu| self.add_processor(cpu, false))?;
+
+        Ok(())
+    }
+
     /// Set an architecture hint to be used for a particular processor. This allows overriding
     /// the detected or reported architecture for the processor object. This is particularly
     /// useful for x86 processors which report as x86-64 processors, or when fuzzing x86 code
@@ -39,4 +103,391 @@ impl Tsffs {
 
         Ok(())
     }
+
+    /// Add a preset set of exceptions to the set of exceptions which are treated as solutions.
+    /// The exceptions in the preset are added to any exceptions which are already configured,
+    /// and individual exceptions can still be added or removed afterward.
+    ///
+    /// Available presets are:
+    ///
+    /// * `x86-fatal` - #DE (0), #UD (6), #DF (8), #TS (10), #NP (11), #SS (12), #GP (13), and
+    ///   #PF (14)
+    /// * `riscv-fatal` - Instruction address misaligned (0), instruction access fault (1),
+    ///   illegal instruction (2), load address misaligned (4), load access fault (5), store
+    ///   address misaligned (6), and store access fault (7)
+    pub fn add_exception_preset(&mut self, preset: *mut c_char) -> Result<()> {
+        let preset = unsafe { CStr::from_ptr(preset) }.to_str()?;
+        debug!(self.as_conf_object(), "add_exception_preset({preset})");
+        let preset = ExceptionPreset::from_str(preset)?;
+        self.exceptions.extend(preset.exceptions());
+
+        Ok(())
+    }
+
+    /// Add the default preset of exceptions for the architecture of `cpu` to the set of
+    /// exceptions which are treated as solutions. This uses the architecture hint for the
+    /// processor if one is set, so hints should be added first. It is an error to call this
+    /// for an architecture with no default preset (ARM and AArch64, whose exception numbers
+    /// are specific to each processor model).
+    pub fn add_default_exceptions(&mut self, cpu: *mut ConfObject) -> Result<()> {
+        debug!(
+            self.as_conf_object(),
+            "add_default_exceptions({:#x})", cpu as usize
+        );
+
+        let architecture = self.architecture_of(cpu)?;
+        let preset = architecture.default_exception_preset().ok_or_else(|| {
+            anyhow!("No default exception preset for architecture {architecture:?}")
+        })?;
+        self.exceptions.extend(preset.exceptions());
+
+        Ok(())
+    }
+
+    /// Set the action taken when the exception `exception` occurs. This takes precedence over
+    /// the `exceptions` and `all_exceptions_are_solutions` attributes for this exception, so
+    /// particular exceptions can be excluded when all exceptions are treated as solutions.
+    ///
+    /// Available actions are:
+    ///
+    /// * `solution` (or `crash`) - The exception is treated as a solution
+    /// * `stop` - The testcase execution is stopped normally and the initial snapshot is
+    ///   restored, without saving a solution
+    /// * `ignore` - The exception is ignored and execution continues
+    pub fn set_exception_action(&mut self, exception: i64, action: *mut c_char) -> Result<()> {
+        let action = unsafe { CStr::from_ptr(action) }.to_str()?;
+        debug!(
+            self.as_conf_object(),
+            "set_exception_action({exception}, {action})"
+        );
+
+        self.exception_actions
+            .insert(exception, ExceptionAction::from_str(action)?);
+
+        Ok(())
+    }
+
+    /// Set the action taken when the start harness is reached again during a testcase
+    /// execution, for example by a retry loop in the target. This does not apply when
+    /// `restore_snapshot_on_stop` is disabled, because the target is then expected to loop
+    /// back to the start harness.
+    ///
+    /// Available actions are:
+    ///
+    /// * `resume` - Execution resumes (the default)
+    /// * `error` - The repeated start is treated as a harness error. Fuzzing is stopped and
+    ///   the simulation is left stopped at the repeated start.
+    /// * `resnapshot` - The initial snapshot is replaced with a snapshot at the repeated
+    ///   start, and execution resumes
+    /// * `stop` - The repeated start is treated as a normal stop of the testcase execution,
+    ///   for targets which loop back to the start harness without reaching a stop harness
+    pub fn set_repeated_start_action(&mut self, action: *mut c_char) -> Result<()> {
+        let action = unsafe { CStr::from_ptr(action) }.to_str()?;
+        debug!(self.as_conf_object(), "set_repeated_start_action({action})");
+
+        self.repeated_start_action = RepeatedStartAction::from_str(action)?;
+
+        Ok(())
+    }
+
+    /// Set a register of the start processor to write a seed value to after each testcase is
+    /// written. Targets which seed a pseudo-random number generator from this register
+    /// then behave the same way for every execution of the same testcase. The register is
+    /// checked to exist when it is first written.
+    pub fn set_seed_register(&mut self, register: *mut c_char, value: u64) -> Result<()> {
+        let register = unsafe { CStr::from_ptr(register) }.to_str()?;
+        debug!(
+            self.as_conf_object(),
+            "set_seed_register({register}, {value:#x})"
+        );
+
+        self.seed_register = Some((register.to_string(), value));
+
+        Ok(())
+    }
+
+    /// Set the registers of the start processor the buffer address and size arguments of the
+    /// magic start harness are read from, for targets which pass the buffer in registers other
+    /// than the architecture's argument registers. The size register contains either the
+    /// pointer to the size or the maximum size, depending on the harness. The registers are
+    /// checked to exist when the start harness is reached.
+    pub fn set_magic_start_registers(
+        &mut self,
+        buffer_register: *mut c_char,
+        size_register: *mut c_char,
+    ) -> Result<()> {
+        let buffer_register = unsafe { CStr::from_ptr(buffer_register) }.to_str()?;
+        let size_register = unsafe { CStr::from_ptr(size_register) }.to_str()?;
+        debug!(
+            self.as_conf_object(),
+            "set_magic_start_registers({buffer_register}, {size_register})"
+        );
+
+        self.magic_start_registers = Some((buffer_register.to_string(), size_register.to_string()));
+
+        Ok(())
+    }
+
+    /// Use the global buffer named by the symbol `symbol` in the ELF executable `executable`
+    /// as the testcase buffer, instead of the buffer passed to the magic start harness. The
+    /// symbol is resolved when this method is called, and its address is translated on the
+    /// start processor when the start harness is reached. The size pointer passed to the
+    /// harness is still written, and the maximum size is the size of the symbol if it has
+    /// one. If the symbol cannot be found, a warning is logged and the buffer passed to the
+    /// harness is used.
+    pub fn set_buffer_symbol(
+        &mut self,
+        executable: *mut c_char,
+        symbol: *mut c_char,
+    ) -> Result<()> {
+        let executable = unsafe { CStr::from_ptr(executable) }.to_str()?;
+        let symbol = unsafe { CStr::from_ptr(symbol) }.to_str()?;
+        debug!(
+            self.as_conf_object(),
+            "set_buffer_symbol({executable}, {symbol})"
+        );
+
+        match Utils::find_elf_symbol(Path::new(executable), symbol)? {
+            Some((address, size)) => {
+                self.buffer_symbol = Some(BufferSymbol {
+                    name: symbol.to_string(),
+                    address,
+                    size,
+                });
+            }
+            None => {
+                warn!(
+                    self.as_conf_object(),
+                    "Symbol {symbol} not found in {executable}, using the buffer passed to the start harness"
+                );
+                self.buffer_symbol = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add a buffer a testcase may be written to instead of the buffer passed to the start
+    /// harness, for harnesses which choose one of several buffers. When additional buffers are
+    /// added, the first byte of each testcase selects the buffer (modulo the number of buffers)
+    /// and the rest of the testcase is written to it. The selector byte is consumed even when
+    /// the buffer passed to the start harness is selected, so a one byte testcase writes an
+    /// empty input. Index 0 is the buffer passed to the start harness, and buffers added with
+    /// this method have indices starting at 1 in the order they are added. The buffer is at
+    /// the physical address `address` and holds at most `maximum_size` bytes. The size of the
+    /// testcase is not written for buffers added with this method, see
+    /// `add_input_buffer_with_size_ptr`.
+    pub fn add_input_buffer(&mut self, address: GenericAddress, maximum_size: u64) -> Result<()> {
+        debug!(
+            self.as_conf_object(),
+            "add_input_buffer({address:#x}, {maximum_size:#x})"
+        );
+
+        if maximum_size == 0 {
+            return Err(anyhow!("Input buffer size must be greater than zero").into());
+        }
+
+        self.input_buffers.push(StartInfo {
+            address: StartPhysicalAddress::WasPhysical(address),
+            contents: Vec::new(),
+            size: StartSize::MaxSize(maximum_size as usize),
+        });
+
+        Ok(())
+    }
+
+    /// Add a buffer a testcase may be written to instead of the buffer passed to the start
+    /// harness, as with `add_input_buffer`. When this buffer is selected, the size of the
+    /// testcase is also written as a pointer-sized integer to the physical address
+    /// `size_address`.
+    pub fn add_input_buffer_with_size_ptr(
+        &mut self,
+        address: GenericAddress,
+        size_address: GenericAddress,
+        maximum_size: u64,
+    ) -> Result<()> {
+        debug!(
+            self.as_conf_object(),
+            "add_input_buffer_with_size_ptr({address:#x}, {size_address:#x}, {maximum_size:#x})"
+        );
+
+        if maximum_size == 0 {
+            return Err(anyhow!("Input buffer size must be greater than zero").into());
+        }
+
+        self.input_buffers.push(StartInfo {
+            address: StartPhysicalAddress::WasPhysical(address),
+            contents: Vec::new(),
+            size: StartSize::SizePtrAndMaxSize {
+                address: StartPhysicalAddress::WasPhysical(size_address),
+                maximum_size: maximum_size as usize,
+            },
+        });
+
+        Ok(())
+    }
+
+    /// Set a register of the start processor to write the index of the selected input buffer
+    /// to after each testcase is written, for harnesses which choose one of several buffers
+    /// based on a selector. Index 0 is the buffer passed to the start harness, see
+    /// `add_input_buffer`.
+    pub fn set_buffer_selector_register(&mut self, register: *mut c_char) -> Result<()> {
+        let register = unsafe { CStr::from_ptr(register) }.to_str()?;
+        debug!(
+            self.as_conf_object(),
+            "set_buffer_selector_register({register})"
+        );
+
+        self.buffer_selector_register = Some(register.to_string());
+
+        Ok(())
+    }
+
+    /// Add a fault to inject into the target on every iteration. After each testcase is
+    /// written to the target, the byte at the physical address `address` is XORed with
+    /// `mask`. This can be used to test how the target handles corrupted memory. Injected
+    /// faults are recorded in the log along with any solution.
+    pub fn add_fault_injection(&mut self, address: GenericAddress, mask: u32) -> Result<()> {
+        debug!(
+            self.as_conf_object(),
+            "add_fault_injection({address:#x}, {mask:#x})"
+        );
+
+        let mask = u8::try_from(mask)
+            .map_err(|_| anyhow!("Fault injection mask {mask:#x} does not fit in one byte"))?;
+
+        self.fault_injections.push(FaultInjection { address, mask });
+
+        Ok(())
+    }
+
+    /// Set the timeout in seconds of virtual time for each iteration. Unlike setting the
+    /// `timeout` attribute directly, the value is validated to be positive and finite. This
+    /// can be called during fuzzing to adjust the timeout without restarting the campaign. The
+    /// new timeout applies starting with the next iteration, the timeout of the iteration
+    /// currently executing is not changed.
+    pub fn set_timeout(&mut self, timeout: f64) -> Result<()> {
+        debug!(self.as_conf_object(), "set_timeout({timeout})");
+
+        if !timeout.is_finite() || timeout <= 0.0 {
+            return Err(
+                anyhow!("Timeout must be a positive number of seconds, got {timeout}").into(),
+            );
+        }
+
+        self.timeout = timeout;
+
+        Ok(())
+    }
+
+    /// Add a range of physical memory to save when a solution is found. The `size` bytes
+    /// starting at the physical address `address` are saved to a file in the
+    /// `memory_dump_directory`, and the path of the file is recorded in the log along with
+    /// the solution. Memory is not saved for timeouts.
+    pub fn add_solution_memory_dump(&mut self, address: GenericAddress, size: u64) -> Result<()> {
+        debug!(
+            self.as_conf_object(),
+            "add_solution_memory_dump({address:#x}, {size:#x})"
+        );
+
+        if size == 0 {
+            return Err(anyhow!("Memory dump size must be greater than zero").into());
+        }
+
+        self.solution_memory_dumps
+            .push(MemoryDumpRange { address, size });
+
+        Ok(())
+    }
+
+    /// Add a range of logical addresses used as a stack guard. When an exception which is
+    /// treated as a solution is a page fault on an address in the range, the solution is
+    /// reported as a stack overflow instead of a generic exception. Page fault addresses are
+    /// only available on x86 and x86-64 processors, and the page fault exception (14) must be
+    /// treated as a solution.
+    pub fn add_stack_guard(&mut self, address: u64, size: u64) -> Result<()> {
+        debug!(
+            self.as_conf_object(),
+            "add_stack_guard({address:#x}, {size:#x})"
+        );
+
+        if size == 0 {
+            return Err(anyhow!("Stack guard size must be greater than zero").into());
+        }
+
+        self.stack_guards.push(StackGuard { address, size });
+
+        Ok(())
+    }
+
+    /// Add an attribute to check for state which is not reset between iterations. The value
+    /// of the attribute `attribute` on the object named `object` is recorded when the initial
+    /// snapshot is saved and compared each time the snapshot is restored. Any difference is
+    /// logged, which helps find device state the snapshot does not cover.
+    pub fn add_tracked_attribute(
+        &mut self,
+        object: *mut c_char,
+        attribute: *mut c_char,
+    ) -> Result<()> {
+        let object = unsafe { CStr::from_ptr(object) }.to_str()?;
+        let attribute = unsafe { CStr::from_ptr(attribute) }.to_str()?;
+        debug!(
+            self.as_conf_object(),
+            "add_tracked_attribute({object}, {attribute})"
+        );
+
+        // Read the attribute once to check that it exists
+        Utils::attribute_repr(object, attribute)?;
+
+        self.tracked_attributes.push(TrackedAttribute {
+            object: object.to_string(),
+            attribute: attribute.to_string(),
+            initial: None,
+        });
+
+        Ok(())
+    }
+
+    /// Add a transform applied to each testcase before it is written to the target. Transforms
+    /// are applied in the order they are added, each to the output of the previous transform,
+    /// so a checksum added before a length prefix is covered by the length. Testcases are
+    /// truncated before transforms are applied so the transformed testcase fits in the
+    /// maximum size.
+    ///
+    /// Available transforms are:
+    ///
+    /// * `length-le8`, `length-le16`, `length-le32`, `length-le64` - Prepend the length as a
+    ///   little-endian integer
+    /// * `length-be16`, `length-be32`, `length-be64` - Prepend the length as a big-endian
+    ///   integer
+    /// * `crc32-le`, `crc32-be` - Append the CRC32 of the input
+    /// * `sum8` - Append the sum of the bytes of the input modulo 256
+    pub fn add_input_transform(&mut self, transform: *mut c_char) -> Result<()> {
+        let transform = unsafe { CStr::from_ptr(transform) }.to_str()?;
+        debug!(self.as_conf_object(), "add_input_transform({transform})");
+        self.input_transforms
+            .push(InputTransform::from_str(transform)?);
+
+        Ok(())
+    }
+
+    /// Set how the testcase buffer after the end of each testcase is filled, for targets
+    /// which read the whole buffer regardless of the size of the input. The size written to
+    /// the target is always the size of the testcase, not including the fill.
+    ///
+    /// Available policies are:
+    ///
+    /// * `none` - The rest of the buffer is left untouched (the default)
+    /// * `zero` - The rest of the buffer is filled with zero bytes
+    /// * `repeat` - The rest of the buffer is filled with repeated copies of the testcase
+    /// * `byte:N` - The rest of the buffer is filled with the byte `N`, for example
+    ///   `byte:0x41`
+    pub fn set_buffer_fill(&mut self, policy: *mut c_char) -> Result<()> {
+        let policy = unsafe { CStr::from_ptr(policy) }.to_str()?;
+        debug!(self.as_conf_object(), "set_buffer_fill({policy})");
+
+        self.buffer_fill = BufferFill::from_str(policy)?;
+
+        Ok(())
+    }
 }
@@ -3,17 +3,21 @@
 
 use crate::{
     state::{SolutionKind, StopReason},
+    util::Utils,
     ManualStartAddress, ManualStartInfo, ManualStartSize, Tsffs,
 };
 use anyhow::{anyhow, Result};
+use flate2::read::GzDecoder;
 use libafl::inputs::HasBytesVec;
+use libafl_bolts::AsSlice;
 use simics::{
     continue_simulation, debug, interface, lookup_file, run_alone, AsConfObject, AttrValue,
     ConfObject, GenericAddress,
 };
 use std::{
     ffi::{c_char, CStr},
-    fs::read,
+    fs::{read, write},
+    io::Read,
 };
 
 #[interface(name = "fuzz")]
@@ -21,7 +25,8 @@ impl Tsffs {
     /// Reproduce a test case execution. This will set the fuzzer's next input through
     /// one execution using the provided file as input instead of taking input from the
     /// fuzzer. It will stop execution at the first stop, timeout, or other solution
-    /// instead of continuing the fuzzing loop.
+    /// instead of continuing the fuzzing loop. If the file name ends with `.gz`, the file is
+    /// decompressed with gzip.
     ///
     /// This can be called during configuration *or* after stopping the fuzzer once a solution
     /// has been found.
@@ -40,6 +45,22 @@ impl Tsffs {
             )
         })?;
 
+        let contents = if testcase_file.extension().is_some_and(|e| e == "gz") {
+            let mut decompressed = Vec::new();
+            GzDecoder::new(contents.as_slice())
+                .read_to_end(&mut decompressed)
+                .map_err(|e| {
+                    anyhow!(
+                        "Failed to decompress repro testcase file {}: {}",
+                        testcase_file.display(),
+                        e
+                    )
+                })?;
+            decompressed
+        } else {
+            contents
+        };
+
         self.repro_testcase = Some(contents);
 
         if self.iterations > 0 {
@@ -97,6 +118,7 @@ impl Tsffs {
                         ManualStartAddress::Physical(size_address)
                     },
                 },
+                directory_table_base: self.manual_start_directory_table_base(cpu)?,
             },
         })?;
 
@@ -138,6 +160,7 @@ impl Tsffs {
                     ManualStartAddress::Physical(testcase_address)
                 },
                 size: ManualStartSize::MaxSize(maximum_size.try_into()?),
+                directory_table_base: self.manual_start_directory_table_base(cpu)?,
             },
         })?;
 
@@ -187,6 +210,7 @@ impl Tsffs {
                     },
                     maximum_size: maximum_size.try_into()?,
                 },
+                directory_table_base: self.manual_start_directory_table_base(cpu)?,
             },
         })?;
 
@@ -232,6 +256,18 @@ impl Tsffs {
         Ok(())
     }
 
+    /// Interface method to halt fuzzing. When this method is called, the current testcase
+    /// execution is stopped as if it had finished executing normally, the fuzzer is shut
+    /// down, and the simulation is left stopped at the current point instead of restoring the
+    /// initial snapshot. Fuzzing cannot be resumed after halting.
+    pub fn halt(&mut self) -> Result<()> {
+        debug!(self.as_conf_object(), "halt");
+
+        self.stop_simulation(StopReason::Halt)?;
+
+        Ok(())
+    }
+
     /// Interface method to manually signal to stop execution with a solution condition.
     /// When this method is called, the current testcase execution will be stopped as if
     /// it had finished executing with an exception or timeout, and the state will be
@@ -247,4 +283,158 @@ impl Tsffs {
 
         Ok(())
     }
+
+    /// Interface method to request that the initial snapshot be discarded and saved again.
+    /// The refresh happens the next time the initial snapshot is restored, so the new
+    /// snapshot is taken at the same point as the original. This allows the simulator to
+    /// reclaim memory accumulated by the snapshot without restarting the campaign.
+    pub fn refresh_snapshot(&mut self) -> Result<()> {
+        debug!(self.as_conf_object(), "refresh_snapshot");
+
+        self.snapshot_refresh_pending = true;
+
+        Ok(())
+    }
+
+    /// Interface method to reset the fuzzer so that a new campaign can be started in the same
+    /// simulation session. The fuzzer is shut down, the initial snapshot is deleted, and the
+    /// testcase buffer, coverage, and statistics are cleared. The simulation is left at its
+    /// current point, and the next start harness reached starts a new campaign as if it were
+    /// the first. Configuration is not changed.
+    pub fn reset(&mut self) -> Result<()> {
+        debug!(self.as_conf_object(), "reset");
+
+        self.reset_campaign()?;
+
+        Ok(())
+    }
+
+    /// Interface method to get the PCs which have been observed to update the coverage map
+    /// entry at `index`, if `record_coverage_index_pcs` is enabled. More than one PC for an
+    /// index indicates a collision in the coverage map.
+    ///
+    /// # Return Value
+    ///
+    /// Returns an [`AttrValue`] list of integers in ascending order.
+    pub fn get_coverage_index_pcs(&mut self, index: u64) -> Result<AttrValue> {
+        debug!(self.as_conf_object(), "get_coverage_index_pcs({index:#x})");
+
+        if !self.record_coverage_index_pcs {
+            return Err(anyhow!("Coverage index PCs are not being recorded"));
+        }
+
+        Ok(self
+            .coverage_index_pcs
+            .get(&index)
+            .map(|pcs| pcs.iter().copied().collect::<Vec<_>>())
+            .unwrap_or_default()
+            .try_into()?)
+    }
+
+    /// Interface method to save the current contents of the coverage map to the file at
+    /// `path`. The map contains the hit counts of the edges covered so far in the current
+    /// testcase execution, and is written as raw bytes in AFL map layout.
+    pub fn save_coverage_map(&mut self, path: *mut c_char) -> Result<()> {
+        let path = Utils::lookup_output_file(unsafe { CStr::from_ptr(path) }.to_str()?)?;
+
+        debug!(
+            self.as_conf_object(),
+            "save_coverage_map({})",
+            path.display()
+        );
+
+        if !self.coverage_tracing {
+            return Err(anyhow!("Coverage tracing is disabled"));
+        }
+
+        let coverage_map = self
+            .coverage_map
+            .get()
+            .ok_or_else(|| anyhow!("Coverage map not set"))?;
+
+        write(&path, coverage_map.as_slice())
+            .map_err(|e| anyhow!("Failed to write coverage map to {}: {}", path.display(), e))?;
+
+        Ok(())
+    }
+
+    /// Interface method to compare two coverage maps saved with `save_coverage_map`, for
+    /// example to see which edges a change to the target added. The maps at `path_a` and
+    /// `path_b` must be the same size. Returns a list of three lists of coverage map indices:
+    /// the indices covered only in the first map, only in the second map, and in both maps.
+    pub fn diff_coverage_maps(
+        &mut self,
+        path_a: *mut c_char,
+        path_b: *mut c_char,
+    ) -> Result<AttrValue> {
+        let path_a = lookup_file(unsafe { CStr::from_ptr(path_a) }.to_str()?)?;
+        let path_b = lookup_file(unsafe { CStr::from_ptr(path_b) }.to_str()?)?;
+
+        debug!(
+            self.as_conf_object(),
+            "diff_coverage_maps({}, {})",
+            path_a.display(),
+            path_b.display()
+        );
+
+        let map_a = read(&path_a)
+            .map_err(|e| anyhow!("Failed to read coverage map {}: {}", path_a.display(), e))?;
+        let map_b = read(&path_b)
+            .map_err(|e| anyhow!("Failed to read coverage map {}: {}", path_b.display(), e))?;
+
+        if map_a.len() != map_b.len() {
+            return Err(anyhow!(
+                "Coverage maps {} ({:#x} bytes) and {} ({:#x} bytes) are not the same size",
+                path_a.display(),
+                map_a.len(),
+                path_b.display(),
+                map_b.len()
+            ));
+        }
+
+        let mut only_a = Vec::new();
+        let mut only_b = Vec::new();
+        let mut common = Vec::new();
+
+        map_a
+            .iter()
+            .zip(map_b.iter())
+            .enumerate()
+            .for_each(|(index, (a, b))| match (*a != 0, *b != 0) {
+                (true, false) => only_a.push(index as u64),
+                (false, true) => only_b.push(index as u64),
+                (true, true) => common.push(index as u64),
+                (false, false) => {}
+            });
+
+        Ok(vec![only_a, only_b, common].try_into()?)
+    }
+
+    /// Interface method to get the campaign metrics in the Prometheus text exposition
+    /// format. These are the same metrics written to `metrics_path` when `metrics_to_file`
+    /// is set.
+    pub fn get_metrics(&mut self) -> Result<AttrValue> {
+        debug!(self.as_conf_object(), "get_metrics()");
+
+        Ok(self.metrics_text()?.try_into()?)
+    }
+
+    /// Interface method to list the micro checkpoints currently saved in the simulation,
+    /// including those not saved by the fuzzer. Micro checkpoints are only used to save the
+    /// initial snapshot on SIMICS 6, so this can be used to check that the initial snapshot
+    /// exists and that no other micro checkpoints have accumulated.
+    ///
+    /// # Return Value
+    ///
+    /// Returns an [`AttrValue`] list of the names of the micro checkpoints, where the
+    /// position of each name is the index of the micro checkpoint.
+    pub fn list_micro_checkpoints(&mut self) -> Result<AttrValue> {
+        debug!(self.as_conf_object(), "list_micro_checkpoints()");
+
+        Ok(Utils::get_micro_checkpoints()?
+            .into_iter()
+            .map(|checkpoint| checkpoint.name)
+            .collect::<Vec<_>>()
+            .try_into()?)
+    }
 }
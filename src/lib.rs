@@ -29,53 +29,60 @@
 #![warn(missing_docs)]
 
 use crate::interfaces::{config::config, fuzz::fuzz};
-use crate::state::SolutionKind;
-#[cfg(simics_version_6)]
 use crate::util::Utils;
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use arch::{Architecture, ArchitectureHint, ArchitectureOperations};
+use flate2::{write::GzEncoder, Compression};
 use fuzzer::{messages::FuzzerMessage, ShutdownMessage, Testcase};
 use indoc::indoc;
+use input::{apply_input_transforms, select_input_buffer, BufferFill, InputTransform};
 use lcov2::Records;
 use libafl::{inputs::HasBytesVec, prelude::ExitKind};
 use libafl_bolts::prelude::OwnedMutSlice;
 use libafl_targets::AFLppCmpLogMap;
-use log::LogMessage;
+use log::{LogMessage, LogMessageAttributeChange};
 use magic::MagicNumber;
 use num_traits::FromPrimitive as _;
 use os::windows::WindowsOsInfo;
 use serde::{Deserialize, Serialize};
-use serde_json::to_writer;
+use serde_json::{json, to_string_pretty, to_writer};
 use simics::{
     break_simulation, class, debug, error, free_attribute, get_class, get_interface,
-    get_processor_number, info, lookup_file, object_clock, run_command, run_python, simics_init,
-    sys::save_flags_t, trace, version_base, warn, write_configuration_to_file, AsConfObject,
-    BreakpointId, ClassCreate, ClassObjectsFinalize, ConfObject, CoreBreakpointMemopHap,
-    CoreControlRegisterWriteHap, CoreExceptionHap, CoreMagicInstructionHap,
-    CoreSimulationStoppedHap, CpuInstrumentationSubscribeInterface, Event, EventClassFlag,
-    FromConfObject, HapHandle, Interface,
+    get_processor_number, info, lookup_file, object_clock, read_byte, run_command, run_python,
+    simics_init, sys::save_flags_t, trace, version_base, warn, write_byte,
+    write_configuration_to_file, AsConfObject, BreakpointId, ClassCreate, ClassObjectsFinalize,
+    ConfObject, CoreBreakpointMemopHap, CoreControlRegisterWriteHap, CoreExceptionHap,
+    CoreMagicInstructionHap, CoreSimulationStoppedHap, CpuInstrumentationSubscribeInterface, Event,
+    EventClassFlag, FromConfObject, HapHandle, Interface,
 };
 #[cfg(simics_version_6)]
 use simics::{
-    discard_future, restore_micro_checkpoint, save_micro_checkpoint, MicroCheckpointFlags,
+    delete_micro_checkpoint, discard_future, restore_micro_checkpoint, save_micro_checkpoint,
+    MicroCheckpointFlags,
 };
 #[cfg(simics_version_7)]
 // NOTE: save_snapshot used because it is a stable alias for both save_snapshot and take_snapshot
 // which is necessary because this module is compatible with base versions which cross the
 // deprecation boundary
-use simics::{restore_snapshot, save_snapshot};
+use simics::{delete_snapshot, restore_snapshot, save_snapshot};
 use source_cov::SourceCache;
-use state::StopReason;
+use state::{ExceptionAction, RepeatedStartAction, SolutionKind, StopReason};
 use std::{
     alloc::{alloc_zeroed, Layout},
     cell::OnceCell,
-    collections::{hash_map::Entry, BTreeSet, HashMap, HashSet},
-    fs::{create_dir_all, remove_dir_all, File},
+    collections::{BTreeSet, HashMap, HashSet},
+    fs::{create_dir_all, remove_dir_all, write, File},
     hash::{DefaultHasher, Hash, Hasher},
+    io::{BufWriter, Write},
+    iter::once,
     path::PathBuf,
     ptr::null_mut,
     str::FromStr,
-    sync::mpsc::{Receiver, Sender},
+    sync::{
+        atomic::AtomicU64,
+        mpsc::{Receiver, Sender},
+        Arc,
+    },
     thread::JoinHandle,
     time::SystemTime,
 };
@@ -89,6 +96,7 @@ use versions::{Requirement, Versioning};
 pub(crate) mod arch;
 pub(crate) mod fuzzer;
 pub(crate) mod haps;
+pub(crate) mod input;
 pub(crate) mod interfaces;
 pub(crate) mod log;
 pub(crate) mod magic;
@@ -98,6 +106,7 @@ pub(crate) mod state;
 pub(crate) mod tracer;
 pub(crate) mod traits;
 pub(crate) mod util;
+pub(crate) mod watchdog;
 
 /// The class name used for all operations interfacing with SIMICS
 
@@ -121,6 +130,14 @@ impl StartPhysicalAddress {
             StartPhysicalAddress::WasPhysical(addr) => *addr,
         }
     }
+
+    /// Get an address of the same kind with a different physical address
+    pub fn with_physical_address(&self, address: u64) -> Self {
+        match self {
+            StartPhysicalAddress::WasVirtual(_) => StartPhysicalAddress::WasVirtual(address),
+            StartPhysicalAddress::WasPhysical(_) => StartPhysicalAddress::WasPhysical(address),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -160,6 +177,9 @@ pub(crate) struct StartInfo {
 pub(crate) struct ManualStartInfo {
     pub address: ManualStartAddress,
     pub size: ManualStartSize,
+    /// The directory table base to use when translating virtual addresses, if not the
+    /// current context of the processor
+    pub directory_table_base: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -191,6 +211,74 @@ impl StartSize {
             StartSize::SizePtrAndMaxSize { address, .. } => Some(address.clone()),
         }
     }
+
+    /// Get a size of the same kind with a different maximum size
+    pub fn with_maximum_size(&self, maximum_size: usize) -> Self {
+        match self {
+            StartSize::SizePtr { address, .. } => StartSize::SizePtr {
+                address: address.clone(),
+                maximum_size,
+            },
+            StartSize::MaxSize(_) => StartSize::MaxSize(maximum_size),
+            StartSize::SizePtrAndMaxSize { address, .. } => StartSize::SizePtrAndMaxSize {
+                address: address.clone(),
+                maximum_size,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+/// A bit flip applied to target memory after each testcase is written, which can be used to
+/// test the target's handling of corrupted state
+pub(crate) struct FaultInjection {
+    /// The physical address of the byte to modify
+    pub address: u64,
+    /// The mask XORed with the byte at `address`
+    pub mask: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+/// A global buffer in the target, resolved from a symbol, which is used as the testcase
+/// buffer instead of the buffer passed to the start harness
+pub(crate) struct BufferSymbol {
+    /// The name of the symbol
+    pub name: String,
+    /// The virtual address of the buffer
+    pub address: u64,
+    /// The size of the buffer in bytes, or 0 if the symbol has no size
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+/// A range of physical memory saved to a file when a solution is found
+pub(crate) struct MemoryDumpRange {
+    /// The physical address of the start of the range
+    pub address: u64,
+    /// The size of the range in bytes
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+/// A range of logical addresses used as a stack guard, where page faults indicate a stack
+/// overflow
+pub(crate) struct StackGuard {
+    /// The logical address of the start of the range
+    pub address: u64,
+    /// The size of the range in bytes
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+/// An attribute whose value is compared against its value at the initial snapshot after the
+/// snapshot is restored, to detect state which is not reset between iterations
+pub(crate) struct TrackedAttribute {
+    /// The name of the object the attribute belongs to
+    pub object: String,
+    /// The name of the attribute
+    pub attribute: String,
+    /// The representation of the value at the initial snapshot, once it is saved
+    pub initial: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -238,6 +326,12 @@ pub(crate) struct Tsffs {
     ///
     /// would treat any page fault as a solution.
     pub exceptions: BTreeSet<i64>,
+    #[class(attribute(optional, default = 0.0))]
+    /// The time in seconds of virtual time execution continues for after an exception which is
+    /// treated as a solution. The solution is only reported if the testcase execution does not
+    /// stop normally within this time, so faults the target recovers from are not reported.
+    /// If 0, solutions are reported as soon as the exception occurs.
+    pub exception_grace_period: f64,
     #[class(attribute(optional))]
     /// The set of breakpoints which are treated as solutions. For example, to set a solution
     /// breakpoint on the address $addr (note the breakpoint set from the Simics command is
@@ -247,11 +341,35 @@ pub(crate) struct Tsffs {
     /// $bp = (bp.memory.break -x $addr)
     /// @tsffs.breakpoints = [simenv.bp]
     pub breakpoints: BTreeSet<BreakpointId>,
+    #[class(attribute(optional))]
+    /// The set of breakpoints which stop the testcase execution normally (i.e. *not* as a
+    /// solution), as if a stop harness was reached. This detects executions which leave the
+    /// fuzzed code without reaching a stop harness, for example by returning from the fuzzed
+    /// function. Stop breakpoints take precedence over `breakpoints` and
+    /// `all_breakpoints_are_solutions`. For example, to stop when the return address $ret is
+    /// executed:
+    ///
+    /// $bp = (bp.memory.break -x $ret)
+    /// @tsffs.stop_breakpoints = [simenv.bp]
+    pub stop_breakpoints: BTreeSet<BreakpointId>,
     #[class(attribute(optional, default = 5.0))]
     /// The timeout in seconds of virtual time for each iteration of the fuzzer. If the virtual
-    /// time timeout is exceeded for a single iteration, the iteration is stopped and the testcase
-    /// is saved as a solution.
+    /// time timeout is exceeded for a single iteration, the iteration is stopped and counted as
+    /// a timeout. The testcase is saved as a solution if `timeouts_are_solutions` is set.
     pub timeout: f64,
+    #[class(attribute(optional, default = false))]
+    /// Whether timeouts are treated as solutions. If set to `True`, testcases which exceed the
+    /// timeout are saved to the solutions directory, which is useful to find hangs and
+    /// infinite loops. By default, timeouts are counted and logged, but not saved.
+    pub timeouts_are_solutions: bool,
+    #[class(attribute(optional, default = 0))]
+    /// The directory table base (for example, the value of CR3 on x86-64) used to translate
+    /// virtual buffer and size addresses passed to the manual start methods. This allows
+    /// giving addresses in an address space other than the one active on the processor when
+    /// the manual start method is called. If set to 0, virtual addresses are translated in
+    /// the current context of the processor. Only supported on x86-64 processors, the manual
+    /// start methods return an error if this is set on other architectures.
+    pub manual_start_directory_table_base: u64,
     #[class(attribute(optional, default = true))]
     /// Whether the fuzzer should start on compiled-in harnesses. If set to `True`, the fuzzer
     /// will start fuzzing when a harness macro is executed.
@@ -260,6 +378,60 @@ pub(crate) struct Tsffs {
     /// Whether the fuzzer should stop on compiled-in harnesses. If set to `True`, the fuzzer
     /// will start fuzzing when a harness macro is executed.
     pub stop_on_harness: bool,
+    #[class(attribute(optional, default = true))]
+    /// Whether the initial snapshot is restored when a testcase execution stops normally. If
+    /// set to `False`, the target is expected to loop back to the start harness itself after
+    /// each stop. The next testcase is written to the buffer found at the first start and
+    /// execution resumes in place, without re-reading the start harness registers. Target state
+    /// is not reset between iterations, and coverage of the code between the stop and the start
+    /// is attributed to the next testcase. The snapshot is always restored after a solution.
+    pub restore_snapshot_on_stop: bool,
+    #[class(attribute(optional, default = false))]
+    /// Whether the buffer and size are read again from the registers each time the magic
+    /// start harness is reached, instead of only at the first start. This only applies when
+    /// `restore_snapshot_on_stop` is `False`, and is needed when the target moves its buffer
+    /// between iterations, for example when the buffer is allocated on the stack. The next
+    /// testcase is written when the start harness is reached instead of when the previous
+    /// execution stops, and the timeout starts at the start harness.
+    pub rediscover_buffer: bool,
+    #[class(attribute(optional, default = 0))]
+    /// The maximum size in bytes of testcases generated by the fuzzer. Testcases larger than
+    /// this size which are not generated by the fuzzer (for example, from the initial corpus)
+    /// are still run, and are truncated to the size of the testcase buffer. If set to 0,
+    /// testcases are only limited by the size of the testcase buffer.
+    pub max_testcase_size: usize,
+    #[class(attribute(optional, default = 0))]
+    /// The offset in bytes from the start of the testcase buffer at which testcases are
+    /// written. This leaves a header at the start of the buffer untouched, and the maximum
+    /// testcase size is reduced by the offset. It is an error for the offset to leave no room
+    /// in the buffer. The offset is applied before `input_alignment`.
+    pub input_offset: u64,
+    #[class(attribute(optional, default = 0))]
+    /// The alignment in bytes of the address testcases are written to. If set, testcases are
+    /// written starting at the first address in the testcase buffer which is a multiple of the
+    /// alignment, and the maximum testcase size is reduced by the number of bytes skipped. It
+    /// is an error for the skipped bytes to leave no room in the buffer. If set to 0 or 1,
+    /// testcases are written at the start of the buffer.
+    pub input_alignment: u64,
+    #[class(attribute(optional, default = false))]
+    /// Whether fuzzing stops at the first solution. If set to `True`, the solution is saved,
+    /// the fuzzer is shut down, and the simulation is left stopped at the point the solution
+    /// occurred instead of restoring the initial snapshot, so the target can be inspected from
+    /// the SIMICS prompt. Timeouts only stop fuzzing if `timeouts_are_solutions` is set.
+    /// Fuzzing cannot be resumed after stopping on a solution.
+    pub stop_on_solution: bool,
+    #[class(attribute(optional, default = false))]
+    /// Whether magic instructions executed from a testcase buffer are ignored. If a target
+    /// executes its input as code, a testcase may contain a magic instruction which would
+    /// otherwise stop the execution early, making coverage misleading.
+    pub ignore_magic_in_buffer: bool,
+    #[class(attribute(optional, default = false))]
+    /// Whether to probe the harness instead of fuzzing. If set to `True`, the target runs once
+    /// without any testcase being written, and the fuzzer logs whether the start and stop
+    /// harnesses (or solution conditions) were reached along with the cycle count at which
+    /// each was reached. The simulation is left stopped after the stop is reached. This is
+    /// useful to check that a harness is correctly configured before starting a campaign.
+    pub probe_harness: bool,
     #[class(attribute(optional, default = 0))]
     /// The index number which is passed to the platform-specific magic instruction HAP
     /// by a compiled-in harness to signal that the fuzzer should start the fuzzing loop.
@@ -292,6 +464,25 @@ pub(crate) struct Tsffs {
     /// run indefinitely. If set to a positive integer, the fuzzer will run until the limit is
     /// reached.
     pub iteration_limit: usize,
+    #[class(attribute(optional))]
+    /// SIMICS CLI commands to run before each testcase is written, after the initial
+    /// snapshot is restored. This can be used for per-iteration configuration of devices
+    /// beyond writing registers. Commands run in order while the simulation is stopped, must
+    /// not run the simulation, and an error in a command stops fuzzing. For example:
+    ///
+    /// @tsffs.pre_testcase_commands = ["board.mb.uart0->rx_fifo_level = 0"]
+    pub pre_testcase_commands: Vec<String>,
+    #[class(attribute(optional))]
+    /// SIMICS CLI commands to run after each testcase execution stops, before the initial
+    /// snapshot is restored. Commands run in order while the simulation is stopped, must not
+    /// run the simulation, and an error in a command stops fuzzing.
+    pub post_testcase_commands: Vec<String>,
+    #[class(attribute(optional, default = 0))]
+    /// The limit on the wall-clock time in seconds to fuzz for. If set to 0, the fuzzer will
+    /// run indefinitely. If set to a positive integer, the fuzzer will stop at the end of the
+    /// first iteration which ends after the limit is reached. This is independent of the
+    /// `timeout` of each iteration, and can be combined with `iteration_limit`.
+    pub time_limit: u64,
     #[class(attribute(optional, default = 8))]
     /// The size of the corpus to generate randomly. If `generate_random_corpus` is set to
     /// `True`, the fuzzer will generate a random corpus of this size before starting the
@@ -328,6 +519,17 @@ pub(crate) struct Tsffs {
     /// Whether coverage reporting should be enabled. When enabled, new edge addresses will
     /// be logged.
     pub coverage_reporting: bool,
+    #[class(attribute(optional, default = true))]
+    /// Whether coverage is traced. When disabled, no coverage or comparison information is
+    /// collected, which makes executions faster but leaves the fuzzer without feedback. This
+    /// is useful for reproducing and triaging solutions, where coverage is not needed.
+    pub coverage_tracing: bool,
+    #[class(attribute(optional, default = Tsffs::COVERAGE_MAP_SIZE))]
+    /// The size of the coverage map in bytes, which must be a power of two. Smaller maps are
+    /// cheaper to process for small targets, and larger maps reduce edge collisions for large
+    /// targets. The size is fixed when the fuzzer starts, and can only be changed for a new
+    /// campaign after a reset.
+    pub coverage_map_size: usize,
     #[class(attribute(optional))]
     /// A set of executable files to tokenize. Tokens will be extracted from these files and
     /// used to drive token mutations of testcases.
@@ -354,6 +556,17 @@ pub(crate) struct Tsffs {
     pub checkpoint_path: PathBuf,
     #[class(attribute(optional, default = true))]
     pub pre_snapshot_checkpoint: bool,
+    #[class(attribute(optional, default = Tsffs::SNAPSHOT_NAME.to_string()))]
+    /// The name the initial snapshot (or micro checkpoint) is saved with. Setting a name
+    /// other than the default allows other snapshots, for example one taken before the target
+    /// boots, to be kept alongside the initial snapshot without conflicting with it.
+    pub initial_snapshot_name: String,
+    #[class(attribute(optional, default = 0))]
+    /// The number of iterations after which the initial snapshot is discarded and saved again
+    /// at the same point, which allows the simulator to reclaim memory accumulated by the
+    /// snapshot during long campaigns. If set to 0, the snapshot is only refreshed when
+    /// requested with the `refresh_snapshot` interface method.
+    pub snapshot_refresh_interval: usize,
     #[class(attribute(optional, default = lookup_file("%simics%")?.join("log.json")))]
     /// The path to the log file which will be used to log the fuzzer's output statistics
     pub log_path: PathBuf,
@@ -365,13 +578,31 @@ pub(crate) struct Tsffs {
     /// Whether to use the initial contents of the testcase buffer as an entry in the corpus
     pub use_initial_as_corpus: bool,
     #[class(attribute(optional, default = false))]
+    /// Whether to record the PCs which update each coverage map index, for debugging
+    /// collisions in the coverage map. The recorded PCs for an index can be retrieved with
+    /// the `get_coverage_index_pcs` interface method. At most 16 PCs are recorded for each
+    /// index to bound memory use.
+    pub record_coverage_index_pcs: bool,
+    #[class(attribute(optional, default = false))]
+    /// Whether to subtract baseline coverage from the coverage of each execution before it is
+    /// reported to the fuzzer. When enabled, the first execution after the initial snapshot
+    /// is taken runs with an empty testcase, and the edges it covers (typically harness and
+    /// initialization code) are removed from the coverage of every later execution. This
+    /// attributes coverage to the effect of the input alone.
+    pub subtract_coverage_baseline: bool,
+    #[class(attribute(optional, default = false))]
     /// Whether to enable extra debug logging for LibAFL
     pub debug_log_libafl: bool,
+    #[class(attribute(optional, default = false))]
+    /// Whether to log every testcase sent from the fuzzer thread to the simulation and every
+    /// execution result received back, with timestamps and testcase sizes, to stderr. This is
+    /// useful when diagnosing a fuzzer which stops making progress.
+    pub debug_log_fuzzer_channel: bool,
     #[class(attribute(optional, default = true))]
     /// Whether to send shut down on stops without reason. This means fuzzing cannot be resumed.
     pub shutdown_on_stop_without_reason: bool,
     #[class(attribute(optional, default = true))]
-    /// Whether to quit on iteration limit
+    /// Whether to quit on iteration limit or time limit
     pub quit_on_iteration_limit: bool,
     #[class(attribute(optional, default = false))]
     /// Whether to save execution traces of test cases which result in a timeout
@@ -391,6 +622,31 @@ pub(crate) struct Tsffs {
     /// directory may be a SIMICS relative path prefixed with "%simics%". If not
     /// provided, "%simics%/execution-traces" will be used by default.
     pub execution_trace_directory: PathBuf,
+    #[class(attribute(optional))]
+    /// The indices of model-specific registers of the start processor to read and log when a
+    /// solution is found. Only x86 and x86-64 processors have model-specific registers. For
+    /// example, to log IA32_EFER:
+    ///
+    /// @tsffs.solution_msrs = [0xc0000080]
+    pub solution_msrs: Vec<u32>,
+    #[class(attribute(optional))]
+    /// The names of additional integer registers of the start processor to read and log when
+    /// a solution is found. For example on x86_64:
+    ///
+    /// @tsffs.solution_registers = ["rax", "rdi", "rsi"]
+    pub solution_registers: Vec<String>,
+    #[class(attribute(optional, default = false))]
+    /// Whether artifacts are compressed with gzip when they are written, to save disk space
+    /// for large inputs. This applies to the metadata files of corpus entries and solutions,
+    /// memory dumps, and execution traces. Compressed memory dumps and execution traces have
+    /// a `.gz` suffix. Testcases themselves are not compressed because the fuzzer reads them
+    /// back from the corpus, but compressed testcases can be passed to `repro`.
+    pub compress_artifacts: bool,
+    #[class(attribute(optional, default = lookup_file("%simics%")?.join("memory-dumps")))]
+    /// The directory to save memory dumps to when a solution is found, if any memory dump
+    /// ranges are configured. This directory may be a SIMICS relative path prefixed with
+    /// "%simics%". If not provided, "%simics%/memory-dumps" will be used by default.
+    pub memory_dump_directory: PathBuf,
     #[class(attribute(optional, default = false))]
     /// Whether execution traces should include just PC (vs instruction text and bytes)
     pub execution_trace_pc_only: bool,
@@ -400,6 +656,25 @@ pub(crate) struct Tsffs {
     #[class(attribute(optional, default = 60))]
     /// The interval in seconds between heartbeat messages
     pub heartbeat_interval: u64,
+    #[class(attribute(optional, default = false))]
+    /// Whether campaign metrics should be written to `metrics_path` in the Prometheus text
+    /// exposition format every `heartbeat_interval` seconds. The file can be collected by
+    /// the node exporter textfile collector to monitor campaigns.
+    pub metrics_to_file: bool,
+    #[class(attribute(optional, default = lookup_file("%simics%")?.join("tsffs.prom")))]
+    /// The path to the file campaign metrics are written to if `metrics_to_file` is set
+    pub metrics_path: PathBuf,
+    #[class(attribute(optional, default = false))]
+    /// Whether the number of cycles, instructions, and wall-clock time taken by each testcase
+    /// execution, and the sequence of magic harnesses reached during it, should be written to
+    /// the log file. This is useful for tuning the timeout and finding slow inputs, but
+    /// produces one log entry per iteration.
+    pub log_execution_times: bool,
+    #[class(attribute(optional, default = 0))]
+    /// The number of seconds of real time without a new testcase being requested after which
+    /// the SIMICS process is killed. This recovers campaigns where the simulator itself (not
+    /// just the target software) deadlocks. If set to 0, the watchdog is disabled.
+    pub watchdog_timeout: u64,
 
     #[class(attribute(optional, default = false))]
     /// Whether symbolic coverage should be used during fuzzing
@@ -424,8 +699,9 @@ pub(crate) struct Tsffs {
     /// executable and debug info files where possible.
     pub symbolic_coverage_system: bool,
     #[class(attribute(optional, default = lookup_file("%simics%")?.join("symbolic-coverage")))]
-    /// Directory in which source files are located. Source files do not need to be arranged in
-    /// the same directory structure as the compiled source, and are looked up by hash.
+    /// Directory in which symbolic coverage is saved. Coverage is saved both as an HTML report
+    /// and as an lcov tracefile named `coverage.info`, which can be used with standard
+    /// coverage tooling.
     pub symbolic_coverage_directory: PathBuf,
 
     /// Handle for the core simulation stopped hap
@@ -461,6 +737,9 @@ pub(crate) struct Tsffs {
     /// Reciever from the fuzzer thread to receive messages from the fuzzer thread
     /// including status messages and structured introspection data like new edge findings.
     fuzzer_messages: OnceCell<Receiver<FuzzerMessage>>,
+    /// The time in milliseconds since the unix epoch at which the fuzzing loop last made
+    /// progress, shared with the watchdog thread
+    watchdog_last_progress: OnceCell<Arc<AtomicU64>>,
 
     // Fuzzer coverage maps
     /// The coverage map
@@ -474,6 +753,9 @@ pub(crate) struct Tsffs {
     /// The registered timeout event which is registered and used to detect timeouts in
     /// virtual time
     timeout_event: OnceCell<Event>,
+    /// The registered event used to report an exception solution when the exception grace
+    /// period elapses
+    grace_event: OnceCell<Event>,
     /// The set of edges which have been seen at least once.
     edges_seen: HashSet<u64>,
     /// A map of the new edges to their AFL indices seen since the last time the fuzzer
@@ -487,14 +769,27 @@ pub(crate) struct Tsffs {
 
     /// The name of the fuzz snapshot, if saved
     snapshot_name: OnceCell<String>,
-    /// The index of the micro checkpoint saved for the fuzzer. Only present if not using
-    /// snapshots.
-    micro_checkpoint_index: OnceCell<i32>,
+    /// Whether a refresh of the initial snapshot was requested
+    snapshot_refresh_pending: bool,
+    /// The number of iterations since the initial snapshot was saved or last refreshed
+    iterations_since_snapshot_refresh: usize,
 
     /// The reason the current stop occurred
     stop_reason: Option<StopReason>,
+    /// The kind of the exception solution reported if the current testcase execution does not
+    /// stop normally before the exception grace period elapses
+    pending_fault: Option<SolutionKind>,
+    /// Whether a stop is currently being handled. Exceptions and breakpoints are ignored
+    /// while this is set, so they cannot replace the stop reason.
+    handling_stop: bool,
     /// The buffer and size information, if saved
     start_info: OnceCell<StartInfo>,
+    /// Whether the next testcase is written when the start harness is next reached, because
+    /// the buffer is being rediscovered
+    buffer_rediscovery_pending: bool,
+    /// The buffer and size information at a repeated start harness, if the initial snapshot
+    /// is to be replaced with a snapshot there when the current execution stops
+    repeated_start_info: Option<StartInfo>,
 
     // #[builder(default = SystemTime::now())]
     /// The time the fuzzer was started at
@@ -514,12 +809,68 @@ pub(crate) struct Tsffs {
     /// Tracked processors. This always includes the start processor, and may include
     /// additional processors that are manually added by the user
     processors: HashMap<i32, Architecture>,
+    /// Actions for individual exceptions, which take precedence over `exceptions` and
+    /// `all_exceptions_are_solutions`
+    exception_actions: HashMap<i64, ExceptionAction>,
+    /// The action taken when the start harness is reached again during a testcase execution
+    repeated_start_action: RepeatedStartAction,
+    /// The name of a register of the start processor and the value written to it after each
+    /// testcase is written, if set
+    seed_register: Option<(String, u64)>,
+    /// Bit flips applied to target memory after each testcase is written
+    fault_injections: Vec<FaultInjection>,
+    /// Ranges of physical memory saved when a solution is found
+    solution_memory_dumps: Vec<MemoryDumpRange>,
+    /// Ranges of logical addresses of stack guard pages. Page faults in these ranges are
+    /// reported as stack overflows.
+    stack_guards: Vec<StackGuard>,
+    /// Attributes checked for changes after each restore of the initial snapshot
+    tracked_attributes: Vec<TrackedAttribute>,
+    /// Transforms applied in order to each testcase before it is written
+    input_transforms: Vec<InputTransform>,
+    /// How the testcase buffer after the end of each testcase is filled
+    buffer_fill: BufferFill,
+    /// The names of the registers the magic start harness buffer address and size arguments
+    /// are read from, if set, instead of the architecture's argument registers
+    magic_start_registers: Option<(String, String)>,
+    /// A global buffer used as the testcase buffer for magic start harnesses, if set
+    buffer_symbol: Option<BufferSymbol>,
+    /// Additional buffers a testcase may be written to, selected by the first byte of each
+    /// testcase
+    input_buffers: Vec<StartInfo>,
+    /// The name of a register of the start processor the index of the selected input buffer
+    /// is written to, if set
+    buffer_selector_register: Option<String>,
+    /// Whether a testcase has been truncated to the size of the testcase buffer
+    testcase_truncated: bool,
     /// A testcase to use for repro
     repro_testcase: Option<Vec<u8>>,
     /// Whether a bookmark has been set for repro mode
     repro_bookmark_set: bool,
     /// Whether the fuzzer is currently stopped in repro mode
     stopped_for_repro: bool,
+    /// The cycle count of the start processor at which the start harness was reached while
+    /// probing the harness
+    probe_start_cycle: Option<i64>,
+    /// Which coverage map entries have been covered by any execution whose execution time was
+    /// logged
+    accumulated_coverage: Vec<bool>,
+    /// The magic harnesses which were handled since the last testcase execution stopped, in
+    /// the order they were reached
+    magic_sequence: Vec<MagicNumber>,
+    /// The PCs observed to update each coverage map index, if recording is enabled. At most
+    /// `COVERAGE_INDEX_PCS_LIMIT` PCs are recorded for each index.
+    coverage_index_pcs: HashMap<u64, BTreeSet<u64>>,
+    /// The coverage map of the baseline execution, if captured
+    coverage_baseline: Option<Vec<u8>>,
+    /// Whether the current execution is the baseline execution
+    capturing_coverage_baseline: bool,
+    /// The cycle count of the start processor and the wall-clock time at which the current
+    /// testcase execution started
+    execution_start: Option<(i64, SystemTime)>,
+    /// The number of instructions executed by the traced processors since the current
+    /// testcase execution started
+    execution_instructions: u64,
     /// The number of iterations which have been executed so far
     iterations: usize,
     /// Whether snapshots are used. Snapshots are used on Simics 7.0.0 and later.
@@ -588,11 +939,6 @@ impl ClassObjectsFinalize for Tsffs {
                     .on_control_register_write(trigger_obj, register_nr, value)
                     .expect("Failed to execute on_control_register_write callback")
             })?;
-        tsffs
-            .coverage_map
-            .set(OwnedMutSlice::from(vec![0; Tsffs::COVERAGE_MAP_SIZE]))
-            .map_err(|_e| anyhow!("Value already set"))?;
-
         tsffs
             .aflpp_cmp_map_ptr
             .set(unsafe { alloc_zeroed(Layout::new::<AFLppCmpLogMap>()) as *mut _ })
@@ -619,6 +965,17 @@ impl ClassObjectsFinalize for Tsffs {
             )
             .map_err(|_e| anyhow!("Value already set"))?;
 
+        tsffs
+            .grace_event
+            .set(
+                Event::builder()
+                    .name(Tsffs::GRACE_EVENT_NAME)
+                    .cls(get_class(CLASS_NAME).expect("Error getting class"))
+                    .flags(EventClassFlag::Sim_EC_No_Flags)
+                    .build(),
+            )
+            .map_err(|_e| anyhow!("Value already set"))?;
+
         // Check whether snapshots should be used. This is a runtime check because the module
         // may be loaded in either Simics 6 or Simics 7.
         let version = version_base()
@@ -642,23 +999,60 @@ impl ClassObjectsFinalize for Tsffs {
 }
 
 impl Tsffs {
-    /// The size of the coverage map in bytes
+    /// The default size of the coverage map in bytes
     pub const COVERAGE_MAP_SIZE: usize = 128 * 1024;
+    /// The maximum number of PCs recorded for each coverage map index
+    pub const COVERAGE_INDEX_PCS_LIMIT: usize = 16;
     /// The name of the registered timeout event
     pub const TIMEOUT_EVENT_NAME: &'static str = "detector_timeout_event";
-    /// The name of the initial snapshot
+    /// The name of the registered exception grace period event
+    pub const GRACE_EVENT_NAME: &'static str = "detector_grace_event";
+    /// The default name of the initial snapshot
     pub const SNAPSHOT_NAME: &'static str = "tsffs-origin-snapshot";
+    /// The name of the lcov tracefile saved in the symbolic coverage directory
+    pub const SYMBOLIC_COVERAGE_LCOV_FILE_NAME: &'static str = "coverage.info";
 }
 
 /// Implementations for controlling the simulation
 impl Tsffs {
-    /// Stop the simulation with a reason
+    /// Stop the simulation with a reason. Only one stop may be pending at a time: the reason
+    /// is consumed when the simulation stopped HAP is handled, and any stop requested before
+    /// then (for example, an exception raised while a timeout is already stopping the
+    /// simulation) is ignored so that each stop is handled exactly once.
     pub fn stop_simulation(&mut self, reason: StopReason) -> Result<()> {
-        let break_string = reason.to_string();
+        if let Some(pending) = self.stop_reason.as_ref() {
+            debug!(
+                self.as_conf_object(),
+                "Ignoring stop with reason {reason:?} while stop with reason {pending:?} is pending"
+            );
+            return Ok(());
+        }
 
-        self.stop_reason = Some(reason);
+        let reason = if let Some(kind) = self.pending_fault.take() {
+            self.cancel_grace_event()?;
+
+            if reason.is_normal_stop() {
+                debug!(
+                    self.as_conf_object(),
+                    "Execution recovered from {kind:?} solution within grace period"
+                );
+            }
+
+            reason.with_pending_fault(kind)
+        } else {
+            reason
+        };
 
-        break_simulation(break_string)?;
+        // The reason is only recorded once the break is requested, so a failed break does
+        // not cause every later stop to be ignored
+        break_simulation(reason.to_string())?;
+
+        // Only magic harnesses which actually stop the simulation are recorded
+        if let StopReason::Magic { magic_number } = &reason {
+            self.magic_sequence.push(*magic_number);
+        }
+
+        self.stop_reason = Some(reason);
 
         Ok(())
     }
@@ -666,6 +1060,205 @@ impl Tsffs {
 
 /// Implementations for common functionality
 impl Tsffs {
+    /// The directory table base to use for translating manual start addresses on the
+    /// processor `cpu`, if one is configured. Returns an error if one is configured but the
+    /// architecture of the processor does not support it.
+    pub fn manual_start_directory_table_base(&self, cpu: *mut ConfObject) -> Result<Option<u64>> {
+        if self.manual_start_directory_table_base == 0 {
+            return Ok(None);
+        }
+
+        ensure!(
+            self.architecture_of(cpu)?.supports_directory_table_base(),
+            "manual_start_directory_table_base is set, but translating with a directory table base is not supported for the architecture of this processor"
+        );
+
+        Ok(Some(self.manual_start_directory_table_base))
+    }
+
+    /// Apply the configured input offset and alignment to the start information found at the
+    /// start of the fuzzing loop, returning start information for the offset and aligned buffer
+    pub fn align_start_info(&self, start_info: StartInfo) -> Result<StartInfo> {
+        if self.input_offset == 0 && self.input_alignment <= 1 {
+            return Ok(start_info);
+        }
+
+        let address = start_info.address.physical_address();
+        let maximum_size = start_info.size.maximum_size();
+        let offset_address = address
+            .checked_add(self.input_offset)
+            .ok_or_else(|| anyhow!("Input offset {:#x} overflows address", self.input_offset))?;
+        let aligned_address = if self.input_alignment <= 1 {
+            offset_address
+        } else {
+            offset_address.next_multiple_of(self.input_alignment)
+        };
+        let padding = (aligned_address - address) as usize;
+
+        if padding >= maximum_size {
+            bail!(
+                "Input offset {:#x} and alignment {:#x} leave no room in the testcase buffer at {:#x} of size {:#x}",
+                self.input_offset,
+                self.input_alignment,
+                address,
+                maximum_size
+            );
+        }
+
+        info!(
+            self.as_conf_object(),
+            "Writing testcases at address {aligned_address:#x} (buffer at {address:#x}) with maximum size {:#x}",
+            maximum_size - padding
+        );
+
+        Ok(StartInfo {
+            address: start_info.address.with_physical_address(aligned_address),
+            contents: start_info.contents.iter().skip(padding).copied().collect(),
+            size: start_info.size.with_maximum_size(maximum_size - padding),
+        })
+    }
+
+    /// Serialize the active configuration as JSON. This includes the configuration set with
+    /// the `config` interface, which has no attributes, the most commonly checked
+    /// attributes, and the start information once the fuzzing loop has started.
+    pub fn configuration_json(&self) -> Result<String> {
+        Ok(to_string_pretty(&json!({
+            "timeout": self.timeout,
+            "timeouts_are_solutions": self.timeouts_are_solutions,
+            "exceptions": self.exceptions,
+            "exception_grace_period": self.exception_grace_period,
+            "all_exceptions_are_solutions": self.all_exceptions_are_solutions,
+            "exception_actions": self.exception_actions,
+            "breakpoints": self.breakpoints,
+            "stop_breakpoints": self.stop_breakpoints,
+            "all_breakpoints_are_solutions": self.all_breakpoints_are_solutions,
+            "start_on_harness": self.start_on_harness,
+            "stop_on_harness": self.stop_on_harness,
+            "restore_snapshot_on_stop": self.restore_snapshot_on_stop,
+            "initial_snapshot_name": self.initial_snapshot_name,
+            "repeated_start_action": self.repeated_start_action,
+            "iteration_limit": self.iteration_limit,
+            "time_limit": self.time_limit,
+            "cmplog": self.cmplog,
+            "coverage_tracing": self.coverage_tracing,
+            "coverage_map_size": self.coverage_map_size,
+            "architecture_hints": self.architecture_hints,
+            "trace_processors": self.processors.keys().collect::<BTreeSet<_>>(),
+            "fault_injections": self.fault_injections,
+            "solution_memory_dumps": self.solution_memory_dumps,
+            "stack_guards": self.stack_guards,
+            "tracked_attributes": self.tracked_attributes,
+            "input_transforms": self.input_transforms,
+            "buffer_fill": self.buffer_fill,
+            "buffer_symbol": self.buffer_symbol,
+            "input_buffers": self.input_buffers,
+            "buffer_selector_register": self.buffer_selector_register,
+            "seed_register": self.seed_register,
+            "magic_start_registers": self.magic_start_registers,
+            "start_processor": self.start_processor_number.get(),
+            "start_info": self.start_info.get(),
+        }))?)
+    }
+
+    /// Read the start information passed to a magic start harness from the registers of the
+    /// start processor, then apply the buffer symbol, input offset, and alignment
+    pub fn magic_start_info(&mut self, magic_number: MagicNumber) -> Result<StartInfo> {
+        let argument_registers = self
+            .magic_start_registers
+            .clone()
+            .map(|(buffer_register, size_register)| vec![buffer_register, size_register])
+            .unwrap_or_default();
+        let start_processor = self
+            .start_processor()
+            .ok_or_else(|| anyhow!("No start processor"))?;
+
+        let start_info = match magic_number {
+            MagicNumber::StartBufferPtrSizePtr => {
+                start_processor.get_magic_start_buffer_ptr_size_ptr(&argument_registers)?
+            }
+            MagicNumber::StartBufferPtrSizeVal => {
+                start_processor.get_magic_start_buffer_ptr_size_val(&argument_registers)?
+            }
+            MagicNumber::StartBufferPtrSizePtrVal => {
+                start_processor.get_magic_start_buffer_ptr_size_ptr_val(&argument_registers)?
+            }
+            MagicNumber::StopNormal => unreachable!("StopNormal is not handled here"),
+            MagicNumber::StopAssert => unreachable!("StopAssert is not handled here"),
+        };
+
+        debug!(self.as_conf_object(), "Start info: {start_info:?}");
+
+        let start_info = self.apply_buffer_symbol(start_info)?;
+
+        self.align_start_info(start_info)
+    }
+
+    /// Whether the testcase buffer is found again each time the magic start harness is
+    /// reached, instead of once at the first start
+    pub fn rediscovering_buffer(&self) -> bool {
+        self.rediscover_buffer && !self.restore_snapshot_on_stop
+    }
+
+    /// Replace the buffer of the start information found by a magic start harness with the
+    /// buffer resolved from a symbol, if one is set. The size pointer passed to the harness is
+    /// still used, and the maximum size is the size of the symbol if it has one.
+    pub fn apply_buffer_symbol(&mut self, start_info: StartInfo) -> Result<StartInfo> {
+        let Some(buffer_symbol) = self.buffer_symbol.clone() else {
+            return Ok(start_info);
+        };
+
+        let start_processor = self
+            .start_processor()
+            .ok_or_else(|| anyhow!("No start processor"))?;
+
+        let physical_address = start_processor
+            .logical_to_physical(buffer_symbol.address, None)
+            .map_err(|e| {
+                anyhow!(
+                    "Failed to translate address {:#x} of buffer symbol {}: {}",
+                    buffer_symbol.address,
+                    buffer_symbol.name,
+                    e
+                )
+            })?;
+
+        let maximum_size = if buffer_symbol.size > 0 {
+            buffer_symbol.size as usize
+        } else {
+            start_info.size.maximum_size()
+        };
+
+        let physical_memory = start_processor.processor_info_v2().get_physical_memory()?;
+        let contents = (0..maximum_size as u64)
+            .map(|i| Ok(read_byte(physical_memory, physical_address + i)?))
+            .collect::<Result<Vec<_>>>()?;
+
+        info!(
+            self.as_conf_object(),
+            "Using buffer symbol {} at {:#x} (physical address {physical_address:#x}) with maximum size {maximum_size:#x}",
+            buffer_symbol.name,
+            buffer_symbol.address
+        );
+
+        Ok(StartInfo {
+            address: StartPhysicalAddress::WasVirtual(physical_address),
+            contents,
+            size: start_info.size.with_maximum_size(maximum_size),
+        })
+    }
+
+    /// Return the architecture of a processor, using the architecture hint for the processor
+    /// if one is set
+    pub fn architecture_of(&self, cpu: *mut ConfObject) -> Result<Architecture> {
+        let cpu_number = get_processor_number(cpu)?;
+
+        if let Some(hint) = self.architecture_hints.get(&cpu_number) {
+            hint.architecture(cpu)
+        } else {
+            Architecture::new(cpu)
+        }
+    }
+
     /// Add a monitored processor to the simulation and whether the processor is the
     /// "start processor" which is the processor running when the fuzzing loop begins
     pub fn add_processor(&mut self, cpu: *mut ConfObject, is_start: bool) -> Result<()> {
@@ -677,13 +1270,9 @@ impl Tsffs {
             cpu_number
         );
 
-        if let Entry::Vacant(e) = self.processors.entry(cpu_number) {
-            let architecture = if let Some(hint) = self.architecture_hints.get(&cpu_number) {
-                hint.architecture(cpu)?
-            } else {
-                Architecture::new(cpu)?
-            };
-            e.insert(architecture);
+        if !self.processors.contains_key(&cpu_number) {
+            let architecture = self.architecture_of(cpu)?;
+            self.processors.insert(cpu_number, architecture);
             let mut cpu_interface: CpuInstrumentationSubscribeInterface = get_interface(cpu)?;
             cpu_interface.register_instruction_after_cb(
                 null_mut(),
@@ -753,9 +1342,10 @@ impl Tsffs {
 
             debug!(self.as_conf_object(), "Saving initial snapshot");
 
-            save_snapshot(Self::SNAPSHOT_NAME)?;
+            let name = self.initial_snapshot_name.clone();
+            self.save_named_snapshot(&name)?;
             self.snapshot_name
-                .set(Self::SNAPSHOT_NAME.to_string())
+                .set(name)
                 .map_err(|_| anyhow!("Snapshot name already set"))?;
         }
 
@@ -777,41 +1367,124 @@ impl Tsffs {
 
             debug!(self.as_conf_object(), "Saving initial micro checkpoint");
 
-            save_micro_checkpoint(
-                Self::SNAPSHOT_NAME,
-                MicroCheckpointFlags::Sim_MC_ID_User | MicroCheckpointFlags::Sim_MC_Persistent,
-            )?;
+            let name = self.initial_snapshot_name.clone();
+            self.save_named_snapshot(&name)?;
 
             self.snapshot_name
-                .set(Self::SNAPSHOT_NAME.to_string())
+                .set(name)
                 .map_err(|_| anyhow!("Snapshot name already set"))?;
+        }
 
-            self.micro_checkpoint_index
-                .set(
-                    Utils::get_micro_checkpoints()?
-                        .iter()
-                        .enumerate()
-                        .find_map(|(i, c)| (c.name == Self::SNAPSHOT_NAME).then_some(i as i32))
-                        .ok_or_else(|| {
-                            anyhow!("No micro checkpoint with just-registered name found")
-                        })?,
-                )
-                .map_err(|_| anyhow!("Micro checkpoint index already set"))?;
+        self.record_tracked_attributes()?;
+
+        Ok(())
+    }
+
+    /// Reset the fuzzer to its state before the fuzzing loop was first started, so that a new
+    /// campaign can be started in the same simulation session. The fuzzer thread is shut
+    /// down, the initial snapshot is deleted, and the start processor, testcase buffer,
+    /// coverage, and statistics are cleared. Configuration is kept.
+    pub fn reset_campaign(&mut self) -> Result<()> {
+        // Clear the coverage map first, so testcases reported to the fuzzer while it shuts
+        // down are not considered interesting
+        if let Some(coverage_map) = self.coverage_map.get_mut() {
+            coverage_map.as_mut_slice().fill(0);
+        }
+
+        self.stop_fuzzer_thread()?;
+        self.cancel_timeout_event()?;
+        self.cancel_grace_event()?;
+
+        // The coverage map is allocated again with the configured size when the fuzzer
+        // is next started
+        self.coverage_map.take();
+
+        if let Some(name) = self.snapshot_name.take() {
+            self.delete_named_snapshot(&name)?;
         }
 
+        self.snapshot_refresh_pending = false;
+        self.iterations_since_snapshot_refresh = 0;
+        self.start_info.take();
+        self.buffer_rediscovery_pending = false;
+        self.repeated_start_info = None;
+        self.start_processor_number.take();
+        self.start_time.take();
+        self.last_heartbeat_time = None;
+        self.stop_reason = None;
+        self.pending_fault = None;
+        self.handling_stop = false;
+        self.coverage_enabled = false;
+        self.cmplog_enabled = false;
+        self.coverage_prev_loc = 0;
+        self.edges_seen.clear();
+        self.edges_seen_since_last.clear();
+        self.execution_trace.0.clear();
+        self.coverage = Records::default();
+        self.accumulated_coverage.clear();
+        self.magic_sequence.clear();
+        self.coverage_index_pcs.clear();
+        self.coverage_baseline = None;
+        self.capturing_coverage_baseline = false;
+        self.execution_start = None;
+        self.execution_instructions = 0;
+        self.probe_start_cycle = None;
+        self.repro_bookmark_set = false;
+        self.stopped_for_repro = false;
+        self.testcase_truncated = false;
+        self.iterations = 0;
+        self.timeouts = 0;
+        self.solutions = 0;
+
         Ok(())
     }
 
     /// Restore the initial snapshot using the configured method (either rev-exec micro checkpoints
     /// or snapshots)
     pub fn restore_initial_snapshot(&mut self) -> Result<()> {
+        let name = self
+            .snapshot_name
+            .get()
+            .cloned()
+            .unwrap_or_else(|| self.initial_snapshot_name.clone());
+
+        // An exception deferred during the previous execution must not be reported for the
+        // next one, even if the simulation stopped without a reason before this restore
+        self.pending_fault = None;
+        self.cancel_grace_event()?;
+
+        self.restore_named_snapshot(&name)
+    }
+
+    /// Save a snapshot (or micro checkpoint) with the name `name`
+    pub fn save_named_snapshot(&mut self, name: &str) -> Result<()> {
         #[cfg(simics_version_7)]
-        restore_snapshot(Self::SNAPSHOT_NAME)?;
+        save_snapshot(name)?;
+
         #[cfg(simics_version_6)]
         {
-            restore_micro_checkpoint(*self.micro_checkpoint_index.get().ok_or_else(|| {
-                anyhow!("Not using snapshots and no micro checkpoint index present")
-            })?)?;
+            save_micro_checkpoint(
+                name,
+                MicroCheckpointFlags::Sim_MC_ID_User | MicroCheckpointFlags::Sim_MC_Persistent,
+            )?;
+
+            Utils::micro_checkpoint_index(name)
+                .map_err(|e| anyhow!("Failed to save micro checkpoint {name}: {e}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Restore the snapshot (or micro checkpoint) saved with the name `name`
+    pub fn restore_named_snapshot(&mut self, name: &str) -> Result<()> {
+        #[cfg(simics_version_7)]
+        restore_snapshot(name)?;
+
+        #[cfg(simics_version_6)]
+        {
+            // Micro checkpoints are restored by index, which changes when an earlier micro
+            // checkpoint is deleted, so the index is looked up each time
+            restore_micro_checkpoint(Utils::micro_checkpoint_index(name)?)?;
 
             discard_future()?;
         }
@@ -819,12 +1492,112 @@ impl Tsffs {
         Ok(())
     }
 
+    /// Delete the snapshot (or micro checkpoint) saved with the name `name`
+    pub fn delete_named_snapshot(&mut self, name: &str) -> Result<()> {
+        #[cfg(simics_version_7)]
+        delete_snapshot(name)?;
+
+        #[cfg(simics_version_6)]
+        {
+            delete_micro_checkpoint(Utils::micro_checkpoint_index(name)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace the initial snapshot with a new snapshot of the current state. This must only be
+    /// called while no testcase execution is in progress, either immediately after restoring
+    /// the initial snapshot, so the new snapshot is taken at the same point, or at a repeated
+    /// start harness once the execution was stopped. Discarding the old snapshot allows the
+    /// simulator to reclaim memory accumulated since it was taken.
+    pub fn refresh_initial_snapshot(&mut self) -> Result<()> {
+        debug!(self.as_conf_object(), "Refreshing initial snapshot");
+
+        let name = self
+            .snapshot_name
+            .get()
+            .cloned()
+            .unwrap_or_else(|| self.initial_snapshot_name.clone());
+
+        self.delete_named_snapshot(&name)?;
+        self.save_named_snapshot(&name)?;
+
+        self.snapshot_refresh_pending = false;
+        self.iterations_since_snapshot_refresh = 0;
+
+        Ok(())
+    }
+
+    /// Refresh the initial snapshot if a refresh was requested or the configured refresh
+    /// interval has elapsed. Called after the initial snapshot is restored.
+    pub fn refresh_initial_snapshot_if_needed(&mut self) -> Result<()> {
+        self.iterations_since_snapshot_refresh += 1;
+
+        if self.snapshot_refresh_pending
+            || (self.snapshot_refresh_interval != 0
+                && self.iterations_since_snapshot_refresh >= self.snapshot_refresh_interval)
+        {
+            self.refresh_initial_snapshot()?;
+        }
+
+        Ok(())
+    }
+
+    /// Record the values of tracked attributes at the initial snapshot
+    pub fn record_tracked_attributes(&mut self) -> Result<()> {
+        self.tracked_attributes.iter_mut().try_for_each(|tracked| {
+            tracked.initial = Some(Utils::attribute_repr(&tracked.object, &tracked.attribute)?);
+            Ok::<(), anyhow::Error>(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Compare the values of tracked attributes against their values at the initial
+    /// snapshot, logging any which differ. Called after the initial snapshot is restored, so
+    /// any difference is state which the snapshot does not reset.
+    pub fn check_tracked_attributes(&mut self) -> Result<()> {
+        let changes = self
+            .tracked_attributes
+            .iter()
+            .filter_map(|tracked| tracked.initial.as_ref().map(|initial| (tracked, initial)))
+            .map(|(tracked, initial)| {
+                Utils::attribute_repr(&tracked.object, &tracked.attribute).map(|current| {
+                    (current != *initial).then(|| LogMessageAttributeChange {
+                        object: tracked.object.clone(),
+                        attribute: tracked.attribute.clone(),
+                        initial: initial.clone(),
+                        current,
+                    })
+                })
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        if !changes.is_empty() {
+            changes.iter().for_each(|change| {
+                warn!(
+                    self.as_conf_object(),
+                    "Attribute {}.{} was not reset by the snapshot: {} -> {}",
+                    change.object,
+                    change.attribute,
+                    change.initial,
+                    change.current
+                );
+            });
+
+            self.log(LogMessage::attribute_changes(self.iterations, changes))?;
+        }
+
+        Ok(())
+    }
+
     /// Whether an initial snapshot has been saved
     pub fn have_initial_snapshot(&self) -> bool {
-        let have = if cfg!(simics_version_7) {
+        let have = if cfg!(simics_version_7) || cfg!(simics_version_6) {
             self.snapshot_name.get().is_some()
-        } else if cfg!(simics_version_6) {
-            self.snapshot_name.get().is_some() && self.micro_checkpoint_index.get().is_some()
         } else {
             error!(self.as_conf_object(), "Unsupported SIMICS version");
             false
@@ -846,6 +1619,12 @@ impl Tsffs {
 impl Tsffs {
     /// Get a testcase from the fuzzer and write it to memory along with, optionally, a size
     pub fn get_and_write_testcase(&mut self) -> Result<()> {
+        self.run_pre_testcase_commands()?;
+
+        if self.subtract_coverage_baseline && self.coverage_baseline.is_none() {
+            return self.write_coverage_baseline_testcase();
+        }
+
         let testcase = self.get_testcase()?;
 
         // TODO: Fix cloning - refcell?
@@ -855,11 +1634,285 @@ impl Tsffs {
             .ok_or_else(|| anyhow!("No start info"))?
             .clone();
 
+        let (buffer_index, testcase_bytes) =
+            select_input_buffer(self.input_buffers.len(), testcase.testcase.bytes());
+
+        let start_info = match buffer_index {
+            0 => start_info,
+            index => self.input_buffers[index - 1].clone(),
+        };
+
+        let mut bytes = if self.input_transforms.is_empty() {
+            testcase_bytes.to_vec()
+        } else {
+            apply_input_transforms(
+                &self.input_transforms,
+                testcase_bytes,
+                start_info.size.maximum_size(),
+            )?
+        };
+
+        let maximum_size = start_info.size.maximum_size();
+
+        if bytes.len() > maximum_size {
+            // Truncation is expected when no maximum testcase size is set, so only the first
+            // truncation is reported as a warning
+            if self.testcase_truncated {
+                debug!(
+                    self.as_conf_object(),
+                    "Truncating testcase of size {:#x} to buffer size {:#x}",
+                    bytes.len(),
+                    maximum_size
+                );
+            } else {
+                warn!(
+                    self.as_conf_object(),
+                    "Truncating testcase of size {:#x} to buffer size {:#x}. Set max_testcase_size to prevent larger testcases from being generated.",
+                    bytes.len(),
+                    maximum_size
+                );
+                self.testcase_truncated = true;
+            }
+
+            bytes.truncate(maximum_size);
+        }
+
+        let seed_register = self.seed_register.clone();
+        let buffer_selector_register = self.buffer_selector_register.clone();
+        let fill = self.buffer_fill.fill(&bytes, maximum_size);
+
         let start_processor = self
             .start_processor()
             .ok_or_else(|| anyhow!("No start processor"))?;
 
-        start_processor.write_start(testcase.testcase.bytes(), &start_info)?;
+        start_processor.write_start(&bytes, &start_info)?;
+
+        if let Some(fill) = fill {
+            // The fill is written after the testcase, so the size written to the target is
+            // still the size of the testcase
+            start_processor.write_physical_memory(
+                start_info.address.physical_address() + bytes.len() as u64,
+                &fill,
+            )?;
+        }
+
+        if let Some((register, value)) = seed_register {
+            start_processor.set_register_value(&register, value)?;
+        }
+
+        if let Some(register) = buffer_selector_register {
+            start_processor.set_register_value(&register, buffer_index as u64)?;
+        }
+
+        self.apply_fault_injections()?;
+
+        if self.log_execution_times {
+            let start_cycle = self
+                .start_processor()
+                .ok_or_else(|| anyhow!("No start processor"))?
+                .cycle()
+                .get_cycle_count()?;
+
+            self.execution_start = Some((start_cycle, SystemTime::now()));
+            self.execution_instructions = 0;
+        }
+
+        Ok(())
+    }
+
+    /// Whether the exception `exception` on the processor `cpu` is a page fault on an address
+    /// in a configured stack guard range, which indicates a stack overflow
+    pub fn is_stack_overflow(&mut self, cpu: *mut ConfObject, exception: i64) -> Result<bool> {
+        if self.stack_guards.is_empty() {
+            return Ok(false);
+        }
+
+        let stack_guards = self.stack_guards.clone();
+        let processor_number = get_processor_number(cpu)?;
+
+        let Some(processor) = self.processors.get_mut(&processor_number) else {
+            return Ok(false);
+        };
+
+        let Some(fault_address) = processor.fault_address(exception)? else {
+            return Ok(false);
+        };
+
+        // NOTE: The offset into the range is compared instead of the end address, so a range
+        // ending at the top of the address space still contains its last address
+        let is_stack_overflow = stack_guards.iter().any(|guard| {
+            fault_address
+                .checked_sub(guard.address)
+                .is_some_and(|offset| offset < guard.size)
+        });
+
+        if is_stack_overflow {
+            info!(
+                self.as_conf_object(),
+                "Page fault at {fault_address:#x} is in a stack guard range, reporting a stack overflow"
+            );
+        }
+
+        Ok(is_stack_overflow)
+    }
+
+    /// Whether the magic instruction just executed by the processor `processor_number` is in
+    /// a testcase buffer, which means the instruction is part of a testcase being executed as
+    /// code rather than part of the harness
+    pub fn magic_in_buffer(&mut self, processor_number: i32) -> Result<bool> {
+        let Some(start_info) = self.start_info.get().cloned() else {
+            return Ok(false);
+        };
+
+        let buffers = once(start_info)
+            .chain(self.input_buffers.iter().cloned())
+            .collect::<Vec<_>>();
+
+        let Some(processor) = self.processors.get_mut(&processor_number) else {
+            return Ok(false);
+        };
+
+        let pc = processor.processor_info_v2().get_program_counter()?;
+
+        // An untranslatable PC cannot be in a buffer, whose addresses are always translated
+        let Ok(physical_pc) = processor.logical_to_physical(pc, None) else {
+            return Ok(false);
+        };
+
+        Ok(buffers.iter().any(|buffer| {
+            let start = buffer.address.physical_address();
+            (start..start + buffer.size.maximum_size() as u64).contains(&physical_pc)
+        }))
+    }
+
+    /// Run the configured SIMICS CLI commands before a testcase is written
+    pub fn run_pre_testcase_commands(&self) -> Result<()> {
+        self.run_testcase_commands(&self.pre_testcase_commands, "pre-testcase")
+    }
+
+    /// Run the configured SIMICS CLI commands after a testcase execution stops
+    pub fn run_post_testcase_commands(&self) -> Result<()> {
+        self.run_testcase_commands(&self.post_testcase_commands, "post-testcase")
+    }
+
+    fn run_testcase_commands(&self, commands: &[String], stage: &str) -> Result<()> {
+        commands.iter().try_for_each(|command| {
+            trace!(self.as_conf_object(), "Running {stage} command '{command}'");
+
+            free_attribute(
+                run_command(command)
+                    .map_err(|e| anyhow!("Failed to run {stage} command '{command}': {e}"))?,
+            )?;
+
+            Ok(())
+        })
+    }
+
+    /// Write an empty testcase for the baseline execution, whose coverage is subtracted from
+    /// the coverage of later executions. No testcase is requested from the fuzzer.
+    fn write_coverage_baseline_testcase(&mut self) -> Result<()> {
+        debug!(
+            self.as_conf_object(),
+            "Writing empty testcase for coverage baseline"
+        );
+
+        let start_info = self
+            .start_info
+            .get()
+            .ok_or_else(|| anyhow!("No start info"))?
+            .clone();
+
+        self.start_processor()
+            .ok_or_else(|| anyhow!("No start processor"))?
+            .write_start(&[], &start_info)?;
+
+        self.capturing_coverage_baseline = true;
+
+        Ok(())
+    }
+
+    /// Called when an execution stops, before its result is reported to the fuzzer. If the
+    /// execution was the baseline execution, its coverage is saved as the baseline and
+    /// cleared, and `true` is returned to indicate the result must not be reported to the
+    /// fuzzer. Otherwise, baseline coverage is removed from the execution's coverage.
+    pub fn apply_coverage_baseline(&mut self) -> Result<bool> {
+        if !self.subtract_coverage_baseline {
+            return Ok(false);
+        }
+
+        let coverage_map = self
+            .coverage_map
+            .get_mut()
+            .ok_or_else(|| anyhow!("Coverage map not set"))?
+            .as_mut_slice();
+
+        if self.capturing_coverage_baseline {
+            self.coverage_baseline = Some(coverage_map.to_vec());
+            coverage_map.fill(0);
+            self.capturing_coverage_baseline = false;
+
+            debug!(
+                self.as_conf_object(),
+                "Captured coverage baseline with {} map entries",
+                self.coverage_baseline
+                    .iter()
+                    .flatten()
+                    .filter(|e| **e != 0)
+                    .count()
+            );
+
+            return Ok(true);
+        }
+
+        if let Some(baseline) = self.coverage_baseline.as_ref() {
+            coverage_map
+                .iter_mut()
+                .zip(baseline)
+                .filter(|(_, b)| **b != 0)
+                .for_each(|(e, _)| *e = 0);
+        }
+
+        Ok(false)
+    }
+
+    /// Apply the configured fault injections to the target's memory. This is done after the
+    /// testcase is written and before execution resumes.
+    pub fn apply_fault_injections(&mut self) -> Result<()> {
+        if self.fault_injections.is_empty() {
+            return Ok(());
+        }
+
+        let fault_injections = self.fault_injections.clone();
+
+        let start_processor = self
+            .start_processor()
+            .ok_or_else(|| anyhow!("No start processor"))?;
+
+        fault_injections.iter().try_for_each(|injection| {
+            if !start_processor.is_mapped(injection.address)? {
+                bail!(
+                    "Fault injection address {:#x} is not mapped in the physical memory of the start processor",
+                    injection.address
+                );
+            }
+
+            Ok::<(), anyhow::Error>(())
+        })?;
+
+        let physical_memory = start_processor.processor_info_v2().get_physical_memory()?;
+
+        fault_injections.iter().try_for_each(|injection| {
+            let value = read_byte(physical_memory, injection.address)?;
+            write_byte(physical_memory, injection.address, value ^ injection.mask)?;
+            trace!(
+                self.as_conf_object(),
+                "Injected fault at {:#x}: {:#x} -> {:#x}",
+                injection.address,
+                value,
+                value ^ injection.mask
+            );
+            Ok::<(), anyhow::Error>(())
+        })?;
 
         Ok(())
     }
@@ -892,9 +1945,7 @@ impl Tsffs {
                 move |_obj| {
                     let tsffs: &'static mut Tsffs = tsffs_ptr.into();
                     tsffs
-                        .stop_simulation(StopReason::Solution {
-                            kind: SolutionKind::Timeout,
-                        })
+                        .stop_simulation(StopReason::Timeout)
                         .expect("Error calling timeout callback");
                 },
             )?;
@@ -936,8 +1987,56 @@ impl Tsffs {
         Ok(())
     }
 
+    /// Start the exception grace period for an exception solution of kind `kind`. The
+    /// solution is reported when the grace period elapses unless the testcase execution
+    /// stops first.
+    pub fn start_grace_period(&mut self, kind: SolutionKind) -> Result<()> {
+        let tsffs_ptr = self.as_conf_object_mut();
+        let start_processor = self
+            .start_processor()
+            .ok_or_else(|| anyhow!("No start processor"))?;
+        let start_processor_cpu = start_processor.cpu();
+        let start_processor_clock = object_clock(start_processor_cpu)?;
+
+        debug!(
+            self.as_conf_object(),
+            "Deferring {kind:?} solution for grace period of {}s", self.exception_grace_period
+        );
+
+        self.pending_fault = Some(kind.clone());
+        self.grace_event
+            .get_mut()
+            .ok_or_else(|| anyhow!("No grace event set"))?
+            .post_time(
+                start_processor_cpu,
+                start_processor_clock,
+                self.exception_grace_period,
+                move |_obj| {
+                    let tsffs: &'static mut Tsffs = tsffs_ptr.into();
+                    tsffs
+                        .stop_simulation(StopReason::Solution { kind: kind.clone() })
+                        .expect("Error calling grace period callback");
+                },
+            )?;
+
+        Ok(())
+    }
+
+    /// Cancel a pending exception grace period event, if there is one
+    pub fn cancel_grace_event(&mut self) -> Result<()> {
+        if let Some(start_processor) = self.start_processor() {
+            let start_processor_cpu = start_processor.cpu();
+            let start_processor_clock = object_clock(start_processor_cpu)?;
+            self.grace_event
+                .get()
+                .ok_or_else(|| anyhow!("No grace event set"))?
+                .cancel_time(start_processor_cpu, start_processor_clock)?;
+        }
+        Ok(())
+    }
+
     pub fn save_symbolic_coverage(&mut self) -> Result<()> {
-        if self.symbolic_coverage_directory.is_dir() {
+        if !self.symbolic_coverage_directory.is_dir() {
             create_dir_all(&self.symbolic_coverage_directory)?;
         }
 
@@ -948,6 +2047,11 @@ impl Tsffs {
         );
 
         self.coverage.to_html(&self.symbolic_coverage_directory)?;
+        write(
+            self.symbolic_coverage_directory
+                .join(Self::SYMBOLIC_COVERAGE_LCOV_FILE_NAME),
+            self.coverage.to_string(),
+        )?;
 
         debug!(
             self.as_conf_object(),
@@ -958,6 +2062,71 @@ impl Tsffs {
         Ok(())
     }
 
+    /// Save the configured ranges of physical memory to files in the memory dump directory,
+    /// returning the paths of the saved files. Memory is streamed to the files, so large
+    /// ranges are not held in memory.
+    pub fn save_solution_memory_dumps(&mut self) -> Result<Vec<PathBuf>> {
+        if self.solution_memory_dumps.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if !self.memory_dump_directory.is_dir() {
+            create_dir_all(&self.memory_dump_directory)?;
+        }
+
+        let ranges = self.solution_memory_dumps.clone();
+        let iteration = self.iterations;
+        let compress = self.compress_artifacts;
+
+        let physical_memory = self
+            .start_processor()
+            .ok_or_else(|| anyhow!("No start processor"))?
+            .processor_info_v2()
+            .get_physical_memory()?;
+
+        ranges
+            .iter()
+            .map(|range| {
+                let dump_path = self.memory_dump_directory.join(format!(
+                    "{}-{:x}-{:x}.bin{}",
+                    iteration,
+                    range.address,
+                    range.size,
+                    if compress { ".gz" } else { "" }
+                ));
+
+                let write_range = |dump: &mut dyn Write| {
+                    (0..range.size).try_for_each(|i| {
+                        let byte = read_byte(physical_memory, range.address + i)?;
+                        dump.write_all(&[byte])?;
+                        Ok::<(), anyhow::Error>(())
+                    })
+                };
+
+                let mut dump = BufWriter::new(File::create(&dump_path)?);
+
+                if compress {
+                    let mut encoder = GzEncoder::new(dump, Compression::default());
+                    write_range(&mut encoder)?;
+                    encoder.finish()?.flush()?;
+                } else {
+                    write_range(&mut dump)?;
+                    dump.flush()?;
+                }
+
+                debug!(
+                    self.as_conf_object(),
+                    "Saved memory dump of {:#x} bytes at {:#x} to {}",
+                    range.size,
+                    range.address,
+                    dump_path.display()
+                );
+
+                Ok(dump_path)
+            })
+            .collect()
+    }
+
     /// Save the current execution trace to a file
     pub fn save_execution_trace(&mut self) -> Result<()> {
         let mut hasher = DefaultHasher::new();
@@ -968,13 +2137,22 @@ impl Tsffs {
             create_dir_all(&self.execution_trace_directory)?;
         }
 
-        let trace_path = self
-            .execution_trace_directory
-            .join(format!("{:x}.json", hash));
+        let trace_path = self.execution_trace_directory.join(format!(
+            "{:x}.json{}",
+            hash,
+            if self.compress_artifacts { ".gz" } else { "" }
+        ));
 
         if !trace_path.exists() {
             let trace_file = File::create(&trace_path)?;
-            to_writer(trace_file, &self.execution_trace)?;
+
+            if self.compress_artifacts {
+                let mut encoder = GzEncoder::new(trace_file, Compression::default());
+                to_writer(&mut encoder, &self.execution_trace)?;
+                encoder.finish()?;
+            } else {
+                to_writer(trace_file, &self.execution_trace)?;
+            }
         }
         Ok(())
     }
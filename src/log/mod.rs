@@ -3,12 +3,23 @@
 
 //! Logging
 
-use crate::{fuzzer::messages::FuzzerMessage, Tsffs};
+use crate::{
+    arch::ArchitectureOperations, fuzzer::messages::FuzzerMessage, magic::MagicNumber,
+    state::SolutionKind, FaultInjection, Tsffs,
+};
 use anyhow::{anyhow, Result};
 use chrono::Utc;
+use libafl_bolts::AsSlice;
 use serde::Serialize;
 use simics::{info, AsConfObject};
-use std::{fs::OpenOptions, io::Write, time::SystemTime};
+use std::{
+    collections::BTreeMap,
+    fs::{rename, write, OpenOptions},
+    io::Write,
+    mem::take,
+    path::PathBuf,
+    time::SystemTime,
+};
 
 #[derive(Clone, Debug, Serialize)]
 pub(crate) struct LogMessageEdge {
@@ -23,6 +34,38 @@ pub(crate) struct LogMessageInteresting {
     pub edges: Vec<LogMessageEdge>,
 }
 
+#[derive(Clone, Debug, Default, Serialize)]
+/// Register values of the start processor at the time of a solution. Each value is `None` if
+/// it could not be read on the processor's architecture.
+pub(crate) struct LogMessageRegisters {
+    pub program_counter: Option<u64>,
+    pub stack_pointer: Option<u64>,
+    pub frame_pointer: Option<u64>,
+    /// The memory surrounding the stack pointer, starting `STACK_CONTEXT_OFFSET` bytes below it
+    pub stack: Option<Vec<u8>>,
+    /// The values of the configured model-specific registers which could be read
+    pub msrs: BTreeMap<u32, u64>,
+    /// The values of the configured additional registers, if all of them could be read
+    pub registers: Option<BTreeMap<String, u64>>,
+}
+
+impl LogMessageRegisters {
+    /// The offset from the stack pointer at which the logged stack memory starts
+    pub const STACK_CONTEXT_OFFSET: i64 = -32;
+    /// The number of bytes of stack memory logged
+    pub const STACK_CONTEXT_SIZE: usize = 64;
+}
+
+#[derive(Clone, Debug, Serialize)]
+/// An attribute whose value after restoring the initial snapshot differs from its value
+/// when the snapshot was saved
+pub(crate) struct LogMessageAttributeChange {
+    pub object: String,
+    pub attribute: String,
+    pub initial: String,
+    pub current: String,
+}
+
 pub(crate) type LogMessageSolution = LogMessageInteresting;
 pub(crate) type LogMessageTimeout = LogMessageInteresting;
 
@@ -47,6 +90,34 @@ pub(crate) enum LogMessage {
         timestamp: String,
         message: LogMessageTimeout,
     },
+    SolutionContext {
+        timestamp: String,
+        kind: SolutionKind,
+        registers: LogMessageRegisters,
+        fault_injections: Vec<FaultInjection>,
+        memory_dumps: Vec<PathBuf>,
+    },
+    AttributeChanges {
+        timestamp: String,
+        iteration: usize,
+        changes: Vec<LogMessageAttributeChange>,
+    },
+    Probe {
+        timestamp: String,
+        start_cycle: Option<i64>,
+        stop_cycle: Option<i64>,
+        stop_reason: String,
+    },
+    Execution {
+        timestamp: String,
+        iteration: usize,
+        exit_kind: String,
+        cycles: i64,
+        instructions: u64,
+        wall_time_us: u128,
+        magic_sequence: Vec<MagicNumber>,
+        new_coverage: bool,
+    },
     Heartbeat {
         iterations: usize,
         solutions: usize,
@@ -85,6 +156,66 @@ impl LogMessage {
         }
     }
 
+    pub(crate) fn solution_context(
+        kind: SolutionKind,
+        registers: LogMessageRegisters,
+        fault_injections: Vec<FaultInjection>,
+        memory_dumps: Vec<PathBuf>,
+    ) -> Self {
+        Self::SolutionContext {
+            timestamp: Utc::now().to_rfc3339(),
+            kind,
+            registers,
+            fault_injections,
+            memory_dumps,
+        }
+    }
+
+    pub(crate) fn attribute_changes(
+        iteration: usize,
+        changes: Vec<LogMessageAttributeChange>,
+    ) -> Self {
+        Self::AttributeChanges {
+            timestamp: Utc::now().to_rfc3339(),
+            iteration,
+            changes,
+        }
+    }
+
+    pub(crate) fn probe(
+        start_cycle: Option<i64>,
+        stop_cycle: Option<i64>,
+        stop_reason: String,
+    ) -> Self {
+        Self::Probe {
+            timestamp: Utc::now().to_rfc3339(),
+            start_cycle,
+            stop_cycle,
+            stop_reason,
+        }
+    }
+
+    pub(crate) fn execution(
+        iteration: usize,
+        exit_kind: String,
+        cycles: i64,
+        instructions: u64,
+        wall_time_us: u128,
+        magic_sequence: Vec<MagicNumber>,
+        new_coverage: bool,
+    ) -> Self {
+        Self::Execution {
+            timestamp: Utc::now().to_rfc3339(),
+            iteration,
+            exit_kind,
+            cycles,
+            instructions,
+            wall_time_us,
+            magic_sequence,
+            new_coverage,
+        }
+    }
+
     pub(crate) fn heartbeat(
         iterations: usize,
         solutions: usize,
@@ -102,6 +233,116 @@ impl LogMessage {
 }
 
 impl Tsffs {
+    /// Log the state of the target at the time a solution was found, before the initial
+    /// snapshot is restored
+    pub fn log_solution_context(&mut self, kind: &SolutionKind) -> Result<()> {
+        let solution_msrs = self.solution_msrs.clone();
+        let solution_registers = self.solution_registers.clone();
+        let registers = self
+            .start_processor()
+            .map(|p| LogMessageRegisters {
+                program_counter: p.processor_info_v2().get_program_counter().ok(),
+                stack_pointer: p.stack_pointer().ok(),
+                frame_pointer: p.frame_pointer().ok(),
+                stack: p
+                    .stack_pointer()
+                    .and_then(|sp| {
+                        p.read_logical_memory(
+                            sp.wrapping_add_signed(LogMessageRegisters::STACK_CONTEXT_OFFSET),
+                            LogMessageRegisters::STACK_CONTEXT_SIZE,
+                        )
+                    })
+                    .ok(),
+                msrs: solution_msrs
+                    .iter()
+                    .filter_map(|index| p.read_msr(*index).ok().map(|value| (*index, value)))
+                    .collect(),
+                registers: (!solution_registers.is_empty())
+                    .then(|| {
+                        p.get_register_values(
+                            &solution_registers
+                                .iter()
+                                .map(|r| r.as_str())
+                                .collect::<Vec<_>>(),
+                        )
+                        .ok()
+                    })
+                    .flatten()
+                    .map(|values| solution_registers.iter().cloned().zip(values).collect()),
+            })
+            .unwrap_or_default();
+
+        let memory_dumps = if matches!(kind, SolutionKind::Timeout) {
+            Vec::new()
+        } else {
+            self.save_solution_memory_dumps()?
+        };
+
+        self.log(LogMessage::solution_context(
+            kind.clone(),
+            registers,
+            self.fault_injections.clone(),
+            memory_dumps,
+        ))
+    }
+
+    /// Log the number of cycles and wall-clock time taken by the testcase execution which
+    /// just stopped, and the magic harnesses reached since the previous execution stopped, if
+    /// execution times are being logged
+    pub fn log_execution_time(&mut self, exit_kind: &str) -> Result<()> {
+        let magic_sequence = take(&mut self.magic_sequence);
+
+        let Some((start_cycle, start_time)) = self.execution_start.take() else {
+            return Ok(());
+        };
+
+        let stop_cycle = self
+            .start_processor()
+            .ok_or_else(|| anyhow!("No start processor"))?
+            .cycle()
+            .get_cycle_count()?;
+
+        let new_coverage = self.accumulate_coverage()?;
+
+        self.log(LogMessage::execution(
+            self.iterations,
+            exit_kind.to_string(),
+            stop_cycle.saturating_sub(start_cycle),
+            self.execution_instructions,
+            start_time.elapsed()?.as_micros(),
+            magic_sequence,
+            new_coverage,
+        ))
+    }
+
+    /// Add the coverage of the testcase execution which just stopped to the coverage
+    /// accumulated over all logged executions, returning whether the execution covered any
+    /// coverage map entry which no earlier logged execution covered
+    fn accumulate_coverage(&mut self) -> Result<bool> {
+        let coverage_map = self
+            .coverage_map
+            .get()
+            .ok_or_else(|| anyhow!("Coverage map not set"))?
+            .as_slice();
+
+        if self.accumulated_coverage.len() != coverage_map.len() {
+            self.accumulated_coverage = vec![false; coverage_map.len()];
+        }
+
+        let mut new_coverage = false;
+
+        coverage_map
+            .iter()
+            .zip(self.accumulated_coverage.iter_mut())
+            .filter(|(hits, seen)| **hits != 0 && !**seen)
+            .for_each(|(_, seen)| {
+                *seen = true;
+                new_coverage = true;
+            });
+
+        Ok(new_coverage)
+    }
+
     pub fn log_messages(&mut self) -> Result<()> {
         let messages = self
             .fuzzer_messages
@@ -234,16 +475,22 @@ impl Tsffs {
             Ok::<(), anyhow::Error>(())
         })?;
 
-        if self.heartbeat {
+        if self.heartbeat || self.metrics_to_file {
             let last = self.last_heartbeat_time.get_or_insert_with(SystemTime::now);
 
             if last.elapsed()?.as_secs() >= self.heartbeat_interval {
-                self.log(LogMessage::heartbeat(
-                    self.iterations,
-                    self.solutions,
-                    self.timeouts,
-                    self.edges_seen.len(),
-                ))?;
+                if self.heartbeat {
+                    self.log(LogMessage::heartbeat(
+                        self.iterations,
+                        self.solutions,
+                        self.timeouts,
+                        self.edges_seen.len(),
+                    ))?;
+                }
+
+                if self.metrics_to_file {
+                    self.write_metrics()?;
+                }
 
                 // Set the last heartbeat time
                 self.last_heartbeat_time = Some(SystemTime::now());
@@ -253,6 +500,79 @@ impl Tsffs {
         Ok(())
     }
 
+    /// Format the campaign metrics in the Prometheus text exposition format
+    pub fn metrics_text(&self) -> Result<String> {
+        let elapsed = self
+            .start_time
+            .get()
+            .map(|t| t.elapsed())
+            .transpose()?
+            .map(|e| e.as_secs_f64())
+            .unwrap_or_default();
+
+        let executions_per_second = if elapsed > 0.0 {
+            self.iterations as f64 / elapsed
+        } else {
+            0.0
+        };
+
+        let metrics: [(&str, &str, &str, String); 6] = [
+            (
+                "tsffs_executions_total",
+                "counter",
+                "Number of testcase executions",
+                self.iterations.to_string(),
+            ),
+            (
+                "tsffs_solutions_total",
+                "counter",
+                "Number of solutions found",
+                self.solutions.to_string(),
+            ),
+            (
+                "tsffs_timeouts_total",
+                "counter",
+                "Number of testcase executions which timed out",
+                self.timeouts.to_string(),
+            ),
+            (
+                "tsffs_edges",
+                "gauge",
+                "Number of edges covered",
+                self.edges_seen.len().to_string(),
+            ),
+            (
+                "tsffs_executions_per_second",
+                "gauge",
+                "Average number of testcase executions per second",
+                executions_per_second.to_string(),
+            ),
+            (
+                "tsffs_elapsed_seconds",
+                "gauge",
+                "Number of seconds since fuzzing started",
+                elapsed.to_string(),
+            ),
+        ];
+
+        Ok(metrics
+            .iter()
+            .map(|(name, kind, help, value)| {
+                format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n")
+            })
+            .collect())
+    }
+
+    /// Write the campaign metrics to the metrics file. The metrics are written to a temporary
+    /// file which is then renamed, so a collector never reads a partially written file.
+    pub fn write_metrics(&self) -> Result<()> {
+        let temporary_path = self.metrics_path.with_extension("prom.tmp");
+        write(&temporary_path, self.metrics_text()?)?;
+        rename(&temporary_path, &self.metrics_path)?;
+
+        Ok(())
+    }
+
     pub fn log<I>(&mut self, item: I) -> Result<()>
     where
         I: Serialize,
@@ -3,7 +3,7 @@
 
 //! Definitions for tracking the state of the fuzzer
 
-use anyhow::{anyhow, Error, Result};
+use anyhow::{anyhow, bail, Error, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::{from_str, to_string};
 use simics::api::ConfObject;
@@ -19,10 +19,69 @@ use crate::{magic::MagicNumber, ManualStartInfo};
 pub(crate) enum SolutionKind {
     Timeout,
     Exception,
+    /// A page fault on an address in a configured stack guard range
+    StackOverflow,
     Breakpoint,
     Manual,
 }
 
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+/// The action taken when a particular exception occurs during a testcase execution
+pub(crate) enum ExceptionAction {
+    /// The exception is treated as a solution
+    Solution,
+    /// The exception stops the testcase execution normally (i.e. *not* as a solution), and
+    /// the initial snapshot is restored
+    Stop,
+    /// The exception is ignored, even if all exceptions are treated as solutions
+    Ignore,
+}
+
+impl FromStr for ExceptionAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "solution" | "crash" => Self::Solution,
+            "stop" => Self::Stop,
+            "ignore" => Self::Ignore,
+            _ => bail!("Unknown exception action: {}", s),
+        })
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The action taken when the start harness is reached again after the initial snapshot was
+/// taken, for example by a retry loop in the target
+pub(crate) enum RepeatedStartAction {
+    /// Execution resumes and the repeated start is otherwise ignored
+    #[default]
+    Resume,
+    /// The repeated start is treated as a harness error. Fuzzing is stopped and the
+    /// simulation is left stopped at the repeated start.
+    Error,
+    /// The repeated start is treated as a normal stop of the testcase execution, and the
+    /// initial snapshot is replaced with a snapshot at the repeated start
+    Resnapshot,
+    /// The repeated start is treated as a normal stop of the testcase execution, for targets
+    /// which loop back to the start harness without reaching a stop harness
+    Stop,
+}
+
+impl FromStr for RepeatedStartAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "resume" => Self::Resume,
+            "error" => Self::Error,
+            "resnapshot" | "re-snapshot" => Self::Resnapshot,
+            "stop" => Self::Stop,
+            _ => bail!("Unknown repeated start action: {}", s),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// Definition of all the reasons the simulator could be stopped by the fuzzer. In general,
 /// callbacks in the fuzzer, for example [`Driver::on_magic_instruction`] may be called
@@ -44,6 +103,33 @@ pub(crate) enum StopReason {
     Solution {
         kind: SolutionKind,
     },
+    /// The virtual time timeout for the current iteration elapsed
+    Timeout,
+    /// Fuzzing was halted. The current iteration is finished as a normal exit, the fuzzer is
+    /// shut down, and the simulation is left stopped.
+    Halt,
+}
+
+impl StopReason {
+    /// Whether this reason stops the testcase execution normally
+    pub fn is_normal_stop(&self) -> bool {
+        matches!(
+            self,
+            StopReason::Magic {
+                magic_number: MagicNumber::StopNormal,
+            } | StopReason::ManualStop
+        )
+    }
+
+    /// The reason a stop is handled with while an exception solution of kind `kind` is
+    /// deferred for the exception grace period. A timeout during the grace period means the
+    /// target did not recover from the exception, so the deferred solution is reported.
+    pub fn with_pending_fault(self, kind: SolutionKind) -> Self {
+        match self {
+            StopReason::Timeout => StopReason::Solution { kind },
+            reason => reason,
+        }
+    }
 }
 
 impl Display for StopReason {
@@ -59,3 +145,39 @@ impl FromStr for StopReason {
         from_str(s).map_err(|e| anyhow!("Failed to deserialize from string: {e}"))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{SolutionKind, StopReason};
+    use crate::magic::MagicNumber;
+
+    #[test]
+    fn test_pending_fault_recovered() {
+        let reason = StopReason::Magic {
+            magic_number: MagicNumber::StopNormal,
+        };
+
+        assert!(reason.is_normal_stop());
+        assert!(matches!(
+            reason.with_pending_fault(SolutionKind::Exception),
+            StopReason::Magic {
+                magic_number: MagicNumber::StopNormal
+            }
+        ));
+        assert!(matches!(
+            StopReason::ManualStop.with_pending_fault(SolutionKind::Exception),
+            StopReason::ManualStop
+        ));
+    }
+
+    #[test]
+    fn test_pending_fault_not_recovered() {
+        assert!(!StopReason::Timeout.is_normal_stop());
+        assert!(matches!(
+            StopReason::Timeout.with_pending_fault(SolutionKind::StackOverflow),
+            StopReason::Solution {
+                kind: SolutionKind::StackOverflow
+            }
+        ));
+    }
+}
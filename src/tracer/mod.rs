@@ -270,6 +270,14 @@ impl Tsffs {
         coverage_map.as_mut_slice()[afl_idx as usize] = cur_byte.0;
         self.coverage_prev_loc = (pc >> 1) % coverage_map.as_slice().len() as u64;
 
+        if self.record_coverage_index_pcs {
+            let pcs = self.coverage_index_pcs.entry(afl_idx).or_default();
+
+            if pcs.len() < Self::COVERAGE_INDEX_PCS_LIMIT {
+                pcs.insert(pc);
+            }
+        }
+
         Ok(())
     }
 
@@ -332,6 +340,14 @@ impl Tsffs {
 
         if self.coverage_enabled {
             if let Some(arch) = self.processors.get_mut(&processor_number) {
+                if self.log_execution_times {
+                    self.execution_instructions += 1;
+                }
+
+                if !self.coverage_tracing {
+                    return Ok(());
+                }
+
                 match arch.trace_pc(handle) {
                     Ok(r) => {
                         if let Some(pc) = r.edge {
@@ -374,7 +390,7 @@ impl Tsffs {
     ) -> Result<()> {
         let processor_number = get_processor_number(cpu)?;
 
-        if self.coverage_enabled && self.cmplog && self.cmplog_enabled {
+        if self.coverage_enabled && self.coverage_tracing && self.cmplog && self.cmplog_enabled {
             if let Some(arch) = self.processors.get_mut(&processor_number) {
                 match arch.trace_cmp(handle) {
                     Ok(r) => {
@@ -1,13 +1,17 @@
 // Copyright (C) 2024 Intel Corporation
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Result;
-use simics::api::{get_attribute, get_object};
+use anyhow::{anyhow, ensure, Result};
+use goblin::elf::Elf;
+use simics::api::{get_attribute, get_object, lookup_file, run_python, ConfObject};
 use simics::FromAttrValueList;
+use std::{
+    fs::read,
+    path::{Path, PathBuf},
+};
 
 #[derive(Debug, Clone, FromAttrValueList)]
 pub(crate) struct MicroCheckpointInfo {
-    #[allow(unused)]
     pub name: String,
     #[allow(unused)]
     pub pages: i64,
@@ -27,4 +31,96 @@ impl Utils {
 
         Ok(checkpoints)
     }
+
+    /// Get the index of the saved micro checkpoint named `name`. The index of a micro
+    /// checkpoint changes when an earlier micro checkpoint is deleted, so it should not be
+    /// kept across deletions.
+    pub fn micro_checkpoint_index(name: &str) -> Result<i32> {
+        Self::get_micro_checkpoints()?
+            .iter()
+            .position(|checkpoint| checkpoint.name == name)
+            .map(|index| index as i32)
+            .ok_or_else(|| anyhow!("No micro checkpoint named {name}"))
+    }
+
+    /// Get the names of all objects which are instances of the class `class_name`
+    pub fn object_names_of_class(class_name: &str) -> Result<Vec<String>> {
+        ensure!(
+            class_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+            "Invalid class name '{class_name}'"
+        );
+
+        let names: Vec<String> = run_python(&format!(
+            "[o.name for o in SIM_object_iterator_for_class('{class_name}')]"
+        ))?
+        .try_into()?;
+
+        Ok(names)
+    }
+
+    /// Get all objects which are instances of the class `class_name`
+    pub fn objects_of_class(class_name: &str) -> Result<Vec<*mut ConfObject>> {
+        Self::object_names_of_class(class_name)?
+            .iter()
+            .map(|name| Ok(get_object(name)?))
+            .collect()
+    }
+
+    /// Get the Python representation of the value of the attribute `attribute` on the object
+    /// named `object_name`. The representation can be compared to detect changes in value.
+    pub fn attribute_repr(object_name: &str, attribute: &str) -> Result<String> {
+        ensure!(
+            object_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_-.[]".contains(c)),
+            "Invalid object name '{object_name}'"
+        );
+        ensure!(
+            attribute
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_'),
+            "Invalid attribute name '{attribute}'"
+        );
+
+        let repr: String = run_python(&format!(
+            "repr(SIM_get_attribute(SIM_get_object('{object_name}'), '{attribute}'))"
+        ))?
+        .try_into()?;
+
+        Ok(repr)
+    }
+
+    /// Resolve the path of a file to be written, which may be a SIMICS path prefixed with
+    /// "%simics%". The file does not need to exist, but its parent directory must.
+    pub fn lookup_output_file(path: &str) -> Result<PathBuf> {
+        let path = Path::new(path);
+
+        match (path.parent(), path.file_name()) {
+            (Some(parent), Some(file_name)) if !parent.as_os_str().is_empty() => {
+                let parent = parent
+                    .to_str()
+                    .ok_or_else(|| anyhow!("Invalid path {}", path.display()))?;
+                Ok(lookup_file(parent)?.join(file_name))
+            }
+            _ => Ok(path.to_path_buf()),
+        }
+    }
+
+    /// Find the symbol named `name` in the symbol table (or dynamic symbol table, if the
+    /// executable is stripped) of the ELF executable at `executable`, returning its address
+    /// and size, or `None` if the executable has no such symbol
+    pub fn find_elf_symbol(executable: &Path, name: &str) -> Result<Option<(u64, u64)>> {
+        let bytes = read(executable)?;
+        let elf = Elf::parse(&bytes)?;
+
+        Ok(elf
+            .syms
+            .iter()
+            .map(|sym| (sym, &elf.strtab))
+            .chain(elf.dynsyms.iter().map(|sym| (sym, &elf.dynstrtab)))
+            .find(|(sym, strtab)| sym.st_value != 0 && strtab.get_at(sym.st_name) == Some(name))
+            .map(|(sym, _)| (sym.st_value, sym.st_size)))
+    }
 }
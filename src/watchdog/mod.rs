@@ -0,0 +1,89 @@
+// Copyright (C) 2024 Intel Corporation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Watchdog which exits the SIMICS process if the fuzzing loop stops making progress
+
+use crate::Tsffs;
+use anyhow::{anyhow, Result};
+use simics::{debug, lookup_file, AsConfObject};
+use std::{
+    process::exit,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread::{sleep, spawn},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Exit code used when the watchdog kills the process
+const WATCHDOG_EXIT_CODE: i32 = 124;
+
+/// Return the current time in milliseconds since the unix epoch
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+impl Tsffs {
+    /// Start the watchdog thread if a watchdog timeout is configured. The watchdog runs
+    /// independently of the simulation, so it can exit the process even if the simulator
+    /// itself is deadlocked.
+    pub fn start_watchdog_thread(&mut self) -> Result<()> {
+        if self.watchdog_timeout == 0 || self.watchdog_last_progress.get().is_some() {
+            return Ok(());
+        }
+
+        let timeout = Duration::from_secs(self.watchdog_timeout);
+        let last_progress = Arc::new(AtomicU64::new(now_millis()));
+        let project = lookup_file("%simics%")?;
+
+        self.watchdog_last_progress
+            .set(last_progress.clone())
+            .map_err(|_| anyhow!("Watchdog already started"))?;
+
+        debug!(
+            self.as_conf_object(),
+            "Starting watchdog thread with timeout {}s",
+            timeout.as_secs()
+        );
+
+        // NOTE: The SIMICS API must not be used from this thread, it is not a SIMICS thread
+        spawn(move || loop {
+            sleep(timeout.min(Duration::from_secs(1)));
+
+            let elapsed = now_millis().saturating_sub(last_progress.load(Ordering::Relaxed));
+
+            if elapsed > timeout.as_millis() as u64 {
+                eprintln!(
+                    "TSFFS watchdog: no fuzzing progress for {}s, exiting. Project: {}",
+                    elapsed / 1000,
+                    project.display()
+                );
+                exit(WATCHDOG_EXIT_CODE);
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Record that the fuzzing loop has made progress, resetting the watchdog. This also
+    /// re-enables the watchdog if it was disabled.
+    pub fn feed_watchdog(&self) {
+        if let Some(last_progress) = self.watchdog_last_progress.get() {
+            last_progress.store(now_millis(), Ordering::Relaxed);
+        }
+    }
+
+    /// Disable the watchdog until it is next fed. Used when the fuzzing loop is stopped on
+    /// purpose, for example when the simulation is stopped from the CLI.
+    pub fn disable_watchdog(&self) {
+        if let Some(last_progress) = self.watchdog_last_progress.get() {
+            // NOTE: The elapsed time saturates to zero while the last progress time is in
+            // the future
+            last_progress.store(u64::MAX, Ordering::Relaxed);
+        }
+    }
+}
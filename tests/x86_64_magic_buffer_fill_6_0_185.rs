@@ -0,0 +1,137 @@
+// Copyright (C) 2024 Intel Corporation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use indoc::indoc;
+use ispm_wrapper::data::ProjectPackage;
+use simics_test::TestEnvSpec;
+use std::path::PathBuf;
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_x86_64_magic_buffer_fill_6_0_185() -> Result<()> {
+    let output = TestEnvSpec::builder()
+        .name("test_x86_64_magic_buffer_fill_6_0_185")
+        .package_crates([PathBuf::from(env!("CARGO_MANIFEST_DIR"))])
+        .packages([
+            ProjectPackage::builder()
+                .package_number(1000)
+                .version("6.0.185")
+                .build(),
+            ProjectPackage::builder()
+                .package_number(1030)
+                .version("6.0.8")
+                .build(),
+            ProjectPackage::builder()
+                .package_number(2096)
+                .version("6.0.73")
+                .build(),
+            ProjectPackage::builder()
+                .package_number(8112)
+                .version("6.0.21")
+                .build(),
+        ])
+        .cargo_target_tmpdir(env!("CARGO_TARGET_TMPDIR"))
+        .directories([PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("rsrc")
+            .join("x86_64-uefi")])
+        .build()
+        .to_env()?
+        .test_python(indoc! {r#"
+            import cli
+            import simics
+
+            simics.SIM_load_module("tsffs")
+
+            tsffs = simics.SIM_create_object(simics.SIM_get_class("tsffs"), "tsffs", [])
+            simics.SIM_set_log_level(tsffs, 2)
+            tsffs.start_on_harness = True
+            tsffs.stop_on_harness = True
+            tsffs.timeout = 3.0
+            tsffs.exceptions = [14]
+            tsffs.generate_random_corpus = True
+            tsffs.iteration_limit = 100
+            tsffs.iface.config.set_buffer_fill("repeat")
+
+            simics.SIM_load_target(
+                "qsp-x86/uefi-shell",  # Target
+                "qsp",  # Namespace
+                [],  # Presets
+                [  # Cmdline args
+                    ["machine:hardware:storage:disk0:image", "minimal_boot_disk.craff"],
+                    ["machine:hardware:processor:class", "x86-goldencove-server"],
+                ],
+            )
+
+            qsp = simics.SIM_get_object("qsp")
+            cpu = conf.qsp.mb.cpu0.core[0][0]
+            buffer = {}
+
+
+            def read_register(name):
+                return cpu.iface.int_register.read(cpu.iface.int_register.get_number(name))
+
+
+            def fail(message):
+                print(message)
+                simics.SIM_run_alone(lambda _: simics.SIM_quit(1), None)
+
+
+            def on_magic(o, e, r):
+                if r == 1:
+                    # The harness passes the buffer address in rsi and the size address
+                    # in rdx, both identity mapped
+                    buffer["address"] = read_register("rsi")
+                    buffer["size_address"] = read_register("rdx")
+                elif r == 4:
+                    size = simics.SIM_read_phys_memory(cpu, buffer["size_address"], 8)
+                    contents = [
+                        simics.SIM_read_phys_memory(cpu, buffer["address"] + i, 1)
+                        for i in range(8)
+                    ]
+                    print("Testcase size", size, "buffer", bytes(contents).hex())
+
+                    # The rest of the buffer is filled with repeated copies of the testcase
+                    if size > 0 and any(
+                        contents[i] != contents[i % size] for i in range(size, 8)
+                    ):
+                        fail("Buffer was not filled with copies of the testcase")
+
+
+            def startup_script_branch():
+                cli.global_cmds.wait_for_global_time(seconds=15.0, _relative=True)
+                qsp.serconsole.con.iface.con_input.input_str("\n")
+                cli.global_cmds.wait_for_global_time(seconds=1.0, _relative=True)
+                qsp.serconsole.con.iface.con_input.input_str("FS0:\n")
+                cli.global_cmds.wait_for_global_time(seconds=1.0, _relative=True)
+                cli.global_cmds.start_agent_manager()
+                qsp.serconsole.con.iface.con_input.input_str(
+                    "SimicsAgent.efi --download "
+                    + simics.SIM_lookup_file("%simics%/test.efi")
+                    + "\n"
+                )
+                cli.global_cmds.wait_for_global_time(seconds=3.0, _relative=True)
+                qsp.serconsole.con.iface.con_input.input_str("test.efi\n")
+
+
+            def exit_script_branch():
+                cli.global_cmds.wait_for_global_time(seconds=240.0, _relative=True)
+                simics.SIM_quit(1)
+
+
+            simics.SIM_hap_add_callback("Core_Magic_Instruction", on_magic, None)
+            cli.sb_create(startup_script_branch)
+            cli.sb_create(exit_script_branch)
+
+            simics.SIM_continue(0)
+            # NOTE: If running from CLI, omit this!
+            simics.SIM_main_loop()
+        "#})?;
+
+    let output_str = String::from_utf8_lossy(&output.stdout);
+
+    println!("{output_str}");
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+// Copyright (C) 2024 Intel Corporation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use indoc::indoc;
+use ispm_wrapper::data::ProjectPackage;
+use simics_test::TestEnvSpec;
+use std::path::PathBuf;
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_x86_64_magic_compress_artifacts_6_0_185() -> Result<()> {
+    let output = TestEnvSpec::builder()
+        .name("test_x86_64_magic_compress_artifacts_6_0_185")
+        .package_crates([PathBuf::from(env!("CARGO_MANIFEST_DIR"))])
+        .packages([
+            ProjectPackage::builder()
+                .package_number(1000)
+                .version("6.0.185")
+                .build(),
+            ProjectPackage::builder()
+                .package_number(1030)
+                .version("6.0.8")
+                .build(),
+            ProjectPackage::builder()
+                .package_number(2096)
+                .version("6.0.73")
+                .build(),
+            ProjectPackage::builder()
+                .package_number(8112)
+                .version("6.0.21")
+                .build(),
+        ])
+        .cargo_target_tmpdir(env!("CARGO_TARGET_TMPDIR"))
+        .directories([PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("rsrc")
+            .join("x86_64-uefi")])
+        .build()
+        .to_env()?
+        .test_python(indoc! {r#"
+            import cli
+            import gzip
+            import json
+            import os
+            import simics
+
+            simics.SIM_load_module("tsffs")
+
+            tsffs = simics.SIM_create_object(simics.SIM_get_class("tsffs"), "tsffs", [])
+            simics.SIM_set_log_level(tsffs, 2)
+            tsffs.start_on_harness = True
+            tsffs.stop_on_harness = True
+            tsffs.timeout = 3.0
+            tsffs.exceptions = [14]
+            tsffs.generate_random_corpus = True
+            tsffs.iteration_limit = 100
+            tsffs.compress_artifacts = True
+            tsffs.save_all_execution_traces = True
+            tsffs.execution_trace_directory = (
+                simics.SIM_lookup_file("%simics%") + "/execution-traces"
+            )
+
+            simics.SIM_load_target(
+                "qsp-x86/uefi-shell",  # Target
+                "qsp",  # Namespace
+                [],  # Presets
+                [  # Cmdline args
+                    ["machine:hardware:storage:disk0:image", "minimal_boot_disk.craff"],
+                    ["machine:hardware:processor:class", "x86-goldencove-server"],
+                ],
+            )
+
+            qsp = simics.SIM_get_object("qsp")
+            stops = []
+
+
+            def fail(message):
+                print(message)
+                simics.SIM_run_alone(lambda _: simics.SIM_quit(1), None)
+
+
+            def on_magic(o, e, r):
+                if r != 4:
+                    return
+
+                stops.append(r)
+
+                # By the 20th stop, the traces of the earlier executions have been saved
+                if len(stops) == 20:
+                    traces = os.listdir(tsffs.execution_trace_directory)
+                    print("Execution traces", traces)
+
+                    if not traces:
+                        fail("No execution traces were saved")
+                    elif any(not trace.endswith(".json.gz") for trace in traces):
+                        fail("Execution traces were not compressed")
+
+                    for trace in traces:
+                        try:
+                            with gzip.open(
+                                os.path.join(tsffs.execution_trace_directory, trace)
+                            ) as f:
+                                json.load(f)
+                        except (OSError, ValueError):
+                            fail("Execution trace " + trace + " is not compressed JSON")
+
+
+            def startup_script_branch():
+                cli.global_cmds.wait_for_global_time(seconds=15.0, _relative=True)
+                qsp.serconsole.con.iface.con_input.input_str("\n")
+                cli.global_cmds.wait_for_global_time(seconds=1.0, _relative=True)
+                qsp.serconsole.con.iface.con_input.input_str("FS0:\n")
+                cli.global_cmds.wait_for_global_time(seconds=1.0, _relative=True)
+                cli.global_cmds.start_agent_manager()
+                qsp.serconsole.con.iface.con_input.input_str(
+                    "SimicsAgent.efi --download "
+                    + simics.SIM_lookup_file("%simics%/test.efi")
+                    + "\n"
+                )
+                cli.global_cmds.wait_for_global_time(seconds=3.0, _relative=True)
+                qsp.serconsole.con.iface.con_input.input_str("test.efi\n")
+
+
+            def exit_script_branch():
+                cli.global_cmds.wait_for_global_time(seconds=240.0, _relative=True)
+                simics.SIM_quit(1)
+
+
+            simics.SIM_hap_add_callback("Core_Magic_Instruction", on_magic, None)
+            cli.sb_create(startup_script_branch)
+            cli.sb_create(exit_script_branch)
+
+            simics.SIM_continue(0)
+            # NOTE: If running from CLI, omit this!
+            simics.SIM_main_loop()
+        "#})?;
+
+    let output_str = String::from_utf8_lossy(&output.stdout);
+
+    println!("{output_str}");
+
+    Ok(())
+}
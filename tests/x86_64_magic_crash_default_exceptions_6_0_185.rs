@@ -0,0 +1,149 @@
+// Copyright (C) 2024 Intel Corporation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use indoc::indoc;
+use ispm_wrapper::data::ProjectPackage;
+use simics_test::TestEnvSpec;
+use std::path::PathBuf;
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_x86_64_magic_crash_default_exceptions_6_0_185() -> Result<()> {
+    let output = TestEnvSpec::builder()
+        .name("test_x86_64_magic_crash_default_exceptions_6_0_185")
+        .package_crates([PathBuf::from(env!("CARGO_MANIFEST_DIR"))])
+        .packages([
+            ProjectPackage::builder()
+                .package_number(1000)
+                .version("6.0.185")
+                .build(),
+            ProjectPackage::builder()
+                .package_number(1030)
+                .version("6.0.8")
+                .build(),
+            ProjectPackage::builder()
+                .package_number(2096)
+                .version("6.0.73")
+                .build(),
+            ProjectPackage::builder()
+                .package_number(8112)
+                .version("6.0.21")
+                .build(),
+        ])
+        .cargo_target_tmpdir(env!("CARGO_TARGET_TMPDIR"))
+        .directories([PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("rsrc")
+            .join("x86_64-crash-uefi")])
+        .build()
+        .to_env()?
+        .test_python(indoc! {r#"
+            import cli
+            import simics
+
+            simics.SIM_load_module("tsffs")
+
+            tsffs = simics.SIM_create_object(simics.SIM_get_class("tsffs"), "tsffs", [])
+            simics.SIM_set_log_level(tsffs, 2)
+            tsffs.start_on_harness = True
+            tsffs.stop_on_harness = True
+            tsffs.timeout = 3.0
+            tsffs.generate_random_corpus = True
+            tsffs.iteration_limit = 100
+            tsffs.use_initial_as_corpus = True
+
+            simics.SIM_load_target(
+                "qsp-x86/uefi-shell",  # Target
+                "qsp",  # Namespace
+                [],  # Presets
+                [  # Cmdline args
+                    ["machine:hardware:storage:disk0:image", "minimal_boot_disk.craff"],
+                    ["machine:hardware:processor:class", "x86-goldencove-server"],
+                ],
+            )
+
+            qsp = simics.SIM_get_object("qsp")
+            tsffs.iface.config.add_default_exceptions(conf.qsp.mb.cpu0.core[0][0])
+
+            # The default exceptions for x86-64 are the fatal x86 exceptions, including #PF
+            # (14) which the harness triggers
+            if sorted(tsffs.exceptions) != [0, 6, 8, 10, 11, 12, 13, 14]:
+                print("Wrong default exceptions", tsffs.exceptions)
+                simics.SIM_quit(1)
+            started = []
+            page_faults = []
+
+
+            def fail(message):
+                print(message)
+                simics.SIM_run_alone(lambda _: simics.SIM_quit(1), None)
+
+
+            def parse_metrics(text):
+                metrics = {}
+                for line in text.splitlines():
+                    if line and not line.startswith('#'):
+                        name, value = line.split()
+                        metrics[name] = float(value)
+                return metrics
+
+
+            def on_magic(o, e, r):
+                if r == 1:
+                    started.append(r)
+
+
+            def on_exception(o, e, exception):
+                # Only count page faults in the testcase executions, not while booting
+                if exception != 14 or not started:
+                    return
+
+                page_faults.append(exception)
+
+                # By the third page fault, the earlier ones have been handled
+                if len(page_faults) == 3:
+                    metrics = parse_metrics(tsffs.iface.fuzz.get_metrics())
+                    print("Metrics", metrics)
+
+                    if metrics["tsffs_solutions_total"] < 1:
+                        fail("Page faults in the default exceptions were not treated as solutions")
+
+
+            def startup_script_branch():
+                cli.global_cmds.wait_for_global_time(seconds=15.0, _relative=True)
+                qsp.serconsole.con.iface.con_input.input_str("\n")
+                cli.global_cmds.wait_for_global_time(seconds=1.0, _relative=True)
+                qsp.serconsole.con.iface.con_input.input_str("FS0:\n")
+                cli.global_cmds.wait_for_global_time(seconds=1.0, _relative=True)
+                cli.global_cmds.start_agent_manager()
+                qsp.serconsole.con.iface.con_input.input_str(
+                    "SimicsAgent.efi --download "
+                    + simics.SIM_lookup_file("%simics%/test.efi")
+                    + "\n"
+                )
+                cli.global_cmds.wait_for_global_time(seconds=3.0, _relative=True)
+                qsp.serconsole.con.iface.con_input.input_str("test.efi\n")
+
+
+            def exit_script_branch():
+                cli.global_cmds.wait_for_global_time(seconds=240.0, _relative=True)
+                simics.SIM_quit(1)
+
+
+            simics.SIM_hap_add_callback("Core_Magic_Instruction", on_magic, None)
+            simics.SIM_hap_add_callback("Core_Exception", on_exception, None)
+            cli.sb_create(startup_script_branch)
+            cli.sb_create(exit_script_branch)
+
+            simics.SIM_continue(0)
+            # NOTE: If running from CLI, omit this!
+            simics.SIM_main_loop()
+        "#})?;
+
+    let output_str = String::from_utf8_lossy(&output.stdout);
+
+    println!("{output_str}");
+
+    Ok(())
+}